@@ -0,0 +1,117 @@
+//! Simulating (and optionally sending) arithmetic instructions
+
+use anyhow::{bail, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::{json, Value};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::Instruction,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use test_program_client::{arithmetic_ix, decode_return_data, ix, Caller, DivRem};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Op {
+    Add,
+    Subtract,
+    Multiply,
+    Max,
+    Min,
+    AbsDiff,
+    Midpoint,
+    Divide,
+    Modulo,
+    DivCeil,
+    DivRem,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CallResult {
+    pub op: Op,
+    pub a: u64,
+    pub b: u64,
+    pub result: Value,
+    pub compute_units: Option<u64>,
+    /// Set when the transaction was sent rather than only simulated
+    pub signature: Option<String>,
+}
+
+/// Build the instruction for `op`, signed by `caller`
+pub fn instruction(op: Op, a: u64, b: u64, caller: &Keypair) -> Instruction {
+    let caller = Caller {
+        signer: caller.pubkey(),
+        history: None,
+    };
+    let caller = Some(&caller);
+    match op {
+        Op::Add => arithmetic_ix(ix::Add { a, b }, caller),
+        Op::Subtract => arithmetic_ix(ix::Subtract { a, b }, caller),
+        Op::Multiply => arithmetic_ix(ix::Multiply { a, b }, caller),
+        Op::Max => arithmetic_ix(ix::Max { a, b }, caller),
+        Op::Min => arithmetic_ix(ix::Min { a, b }, caller),
+        Op::AbsDiff => arithmetic_ix(ix::AbsDiff { a, b }, caller),
+        Op::Midpoint => arithmetic_ix(ix::Midpoint { a, b }, caller),
+        Op::Divide => arithmetic_ix(ix::Divide { a, b }, caller),
+        Op::Modulo => arithmetic_ix(ix::Modulo { a, b }, caller),
+        Op::DivCeil => arithmetic_ix(ix::DivCeil { a, b }, caller),
+        Op::DivRem => arithmetic_ix(ix::DivRem { a, b }, caller),
+    }
+}
+
+/// Decode return data into JSON according to the instruction's return type
+pub fn decode_result(op: Op, data: &[u8]) -> Result<Value> {
+    Ok(match op {
+        Op::DivRem => {
+            let DivRem {
+                quotient,
+                remainder,
+            } = decode_return_data(data)?;
+            json!({ "quotient": quotient, "remainder": remainder })
+        }
+        _ => json!(decode_return_data::<u64>(data)?),
+    })
+}
+
+pub fn call(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    op: Op,
+    a: u64,
+    b: u64,
+    send: bool,
+) -> Result<CallResult> {
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction(op, a, b, payer)],
+        Some(&payer.pubkey()),
+        &[payer],
+        rpc.get_latest_blockhash()?,
+    );
+    let simulation = rpc.simulate_transaction(&tx)?.value;
+    if let Some(err) = simulation.err {
+        let logs = simulation.logs.unwrap_or_default().join("\n");
+        bail!("simulation failed: {err:?}\n{logs}");
+    }
+    // All-zero return data, such as a result of 0, is reported as absent
+    let return_data = match simulation.return_data {
+        Some(return_data) => STANDARD.decode(return_data.data.0)?,
+        None => Vec::new(),
+    };
+    let result = decode_result(op, &return_data)?;
+    let signature = if send {
+        Some(rpc.send_and_confirm_transaction(&tx)?.to_string())
+    } else {
+        None
+    };
+    Ok(CallResult {
+        op,
+        a,
+        b,
+        result,
+        compute_units: simulation.units_consumed,
+        signature,
+    })
+}
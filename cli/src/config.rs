@@ -0,0 +1,73 @@
+//! Resolution of the RPC endpoint and signing keypair
+
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    signature::{read_keypair_file, Keypair},
+};
+
+pub const RPC_URL_ENV: &str = "TEST_PROGRAM_RPC_URL";
+pub const KEYPAIR_ENV: &str = "DEPLOYER_KEYPAIR";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Settings {
+    pub rpc_url: String,
+    pub keypair_path: String,
+}
+
+impl Settings {
+    /// Explicit values (flags or environment) win over the Solana CLI config
+    /// file, which in turn falls back to its own defaults.
+    pub fn resolve(
+        url: Option<&str>,
+        keypair: Option<&str>,
+        config_file: Option<&str>,
+    ) -> Result<Self> {
+        let config_file = config_file
+            .map(str::to_string)
+            .or_else(|| solana_cli_config::CONFIG_FILE.clone());
+        let cli_config = match config_file {
+            Some(path) if Path::new(&path).exists() => solana_cli_config::Config::load(&path)
+                .with_context(|| format!("failed to load Solana CLI config {path}"))?,
+            _ => solana_cli_config::Config::default(),
+        };
+        Ok(Self {
+            rpc_url: url.map(normalize_url).unwrap_or(cli_config.json_rpc_url),
+            keypair_path: keypair
+                .map(str::to_string)
+                .unwrap_or(cli_config.keypair_path),
+        })
+    }
+
+    pub fn rpc_client(&self) -> RpcClient {
+        RpcClient::new_with_commitment(self.rpc_url.clone(), CommitmentConfig::confirmed())
+    }
+
+    pub fn keypair(&self) -> Result<Keypair> {
+        read_keypair_file(&self.keypair_path)
+            .map_err(|err| anyhow!("failed to read keypair {}: {err}", self.keypair_path))
+    }
+}
+
+/// Expand the cluster monikers accepted by the Solana CLI
+pub fn normalize_url(url: &str) -> String {
+    match url {
+        "localhost" | "l" => "http://127.0.0.1:8899",
+        "devnet" | "d" => "https://api.devnet.solana.com",
+        "testnet" | "t" => "https://api.testnet.solana.com",
+        "mainnet-beta" | "m" => "https://api.mainnet-beta.solana.com",
+        other => other,
+    }
+    .to_string()
+}
+
+/// Hide query strings, which commonly carry RPC provider API keys
+pub fn redact_url(url: &str) -> String {
+    match url.split_once('?') {
+        Some((base, _)) => format!("{base}?<redacted>"),
+        None => url.to_string(),
+    }
+}
@@ -0,0 +1,93 @@
+//! Executable hashes computed the same way as `solana-verify`
+//!
+//! `solana-verify` hashes the program binary with SHA-256 after stripping
+//! trailing zero bytes, since on-chain program data accounts are zero-padded
+//! past the end of the ELF. The same rule is applied to local builds and to
+//! deployed program data so the two can be compared directly.
+
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Serialize;
+use solana_client::rpc_client::RpcClient;
+use solana_loader_v3_interface::{get_program_data_address, state::UpgradeableLoaderState};
+use solana_sdk::{hash::hash, pubkey::Pubkey};
+
+const ELF_MAGIC: &[u8] = b"\x7fELF";
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct HashComparison {
+    pub local_hash: String,
+    pub onchain_hash: String,
+    /// Where the deployed binary was read from
+    pub source: String,
+    pub matches: bool,
+}
+
+/// SHA-256 of `binary` with trailing zero padding removed, as lowercase hex
+pub fn executable_hash(binary: &[u8]) -> String {
+    let end = binary
+        .iter()
+        .rposition(|byte| *byte != 0)
+        .map_or(0, |index| index + 1);
+    hash(&binary[..end])
+        .to_bytes()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Return the ELF stored in upgradeable loader program data, or `data`
+/// unchanged if it is already a bare ELF
+pub fn strip_program_data_header(data: &[u8]) -> Result<&[u8]> {
+    if data.starts_with(ELF_MAGIC) {
+        return Ok(data);
+    }
+    let metadata_len = UpgradeableLoaderState::size_of_programdata_metadata();
+    match bincode::deserialize::<UpgradeableLoaderState>(data) {
+        Ok(UpgradeableLoaderState::ProgramData { .. }) if data.len() >= metadata_len => {
+            Ok(&data[metadata_len..])
+        }
+        _ => bail!("data is neither an ELF binary nor upgradeable loader program data"),
+    }
+}
+
+/// Read a deployed binary from a dump file.
+///
+/// Accepts the output of `solana program dump` (a bare ELF), a raw program
+/// data account, or the JSON written by `solana account --output json`.
+pub fn read_dump(path: &Path) -> Result<Vec<u8>> {
+    let contents =
+        std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let data = match serde_json::from_slice::<serde_json::Value>(&contents) {
+        Ok(json) => {
+            let encoded = json["account"]["data"][0]
+                .as_str()
+                .context("JSON dump has no base64 account.data")?;
+            STANDARD.decode(encoded)?
+        }
+        Err(_) => contents,
+    };
+    Ok(strip_program_data_header(&data)?.to_vec())
+}
+
+/// Fetch the deployed binary of `program_id` through its program data account
+pub fn fetch_program(rpc: &RpcClient, program_id: &Pubkey) -> Result<Vec<u8>> {
+    let program_data = get_program_data_address(program_id);
+    let account = rpc
+        .get_account(&program_data)
+        .with_context(|| format!("failed to fetch program data {program_data}"))?;
+    Ok(strip_program_data_header(&account.data)?.to_vec())
+}
+
+pub fn compare(local: &[u8], onchain: &[u8], source: String) -> HashComparison {
+    let local_hash = executable_hash(local);
+    let onchain_hash = executable_hash(onchain);
+    HashComparison {
+        matches: local_hash == onchain_hash,
+        local_hash,
+        onchain_hash,
+        source,
+    }
+}
@@ -0,0 +1,16 @@
+//! Operational tooling for `test_program`, replacing the Node deploy scripts
+//!
+//! Every command prints a single JSON document to stdout.
+
+pub mod call;
+pub mod config;
+pub mod hash;
+pub mod loader;
+pub mod status;
+pub mod verify;
+
+/// Artifact produced by `anchor build --verifiable`
+pub const DEFAULT_PROGRAM_SO: &str = "target/verifiable/test_program.so";
+pub const DEFAULT_PROGRAM_KEYPAIR: &str = "target/deploy/test_program-keypair.json";
+
+pub const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
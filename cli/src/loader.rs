@@ -0,0 +1,120 @@
+//! Program deployment through the upgradeable BPF loader
+
+use anyhow::{bail, Result};
+use solana_client::rpc_client::RpcClient;
+use solana_loader_v3_interface::{
+    get_program_data_address, instruction as bpf_loader_upgradeable, state::UpgradeableLoaderState,
+};
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    transaction::Transaction,
+};
+
+/// Bytes written per buffer `Write` transaction, leaving room for the
+/// signature, account keys and compute budget instruction
+pub const WRITE_CHUNK_LEN: usize = 900;
+
+pub struct Loader<'a> {
+    pub rpc: &'a RpcClient,
+    /// Fee payer, buffer authority and program upgrade authority
+    pub payer: &'a Keypair,
+    pub compute_unit_price: Option<u64>,
+}
+
+impl Loader<'_> {
+    fn send(&self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<Signature> {
+        let mut all_instructions = Vec::with_capacity(instructions.len() + 1);
+        if let Some(price) = self.compute_unit_price {
+            all_instructions.push(ComputeBudgetInstruction::set_compute_unit_price(price));
+        }
+        all_instructions.extend_from_slice(instructions);
+        let mut all_signers = vec![self.payer];
+        all_signers.extend_from_slice(signers);
+        let tx = Transaction::new_signed_with_payer(
+            &all_instructions,
+            Some(&self.payer.pubkey()),
+            &all_signers,
+            self.rpc.get_latest_blockhash()?,
+        );
+        Ok(self.rpc.send_and_confirm_transaction(&tx)?)
+    }
+
+    /// Upload `elf` into a new buffer account, returning its address
+    pub fn write_buffer(&self, elf: &[u8]) -> Result<Pubkey> {
+        let buffer = Keypair::new();
+        let authority = self.payer.pubkey();
+        let lamports = self.rpc.get_minimum_balance_for_rent_exemption(
+            UpgradeableLoaderState::size_of_buffer(elf.len()),
+        )?;
+        let create = bpf_loader_upgradeable::create_buffer(
+            &authority,
+            &buffer.pubkey(),
+            &authority,
+            lamports,
+            elf.len(),
+        )?;
+        self.send(&create, &[&buffer])?;
+        for (index, chunk) in elf.chunks(WRITE_CHUNK_LEN).enumerate() {
+            let offset = (index * WRITE_CHUNK_LEN) as u32;
+            let write =
+                bpf_loader_upgradeable::write(&buffer.pubkey(), &authority, offset, chunk.to_vec());
+            self.send(&[write], &[])?;
+        }
+        Ok(buffer.pubkey())
+    }
+
+    /// Deploy `elf` as a new program at the address of `program`
+    pub fn deploy(&self, program: &Keypair, elf: &[u8]) -> Result<Signature> {
+        if self.rpc.get_account(&program.pubkey()).is_ok() {
+            bail!("program {} already exists; use `upgrade`", program.pubkey());
+        }
+        let buffer = self.write_buffer(elf)?;
+        let lamports = self
+            .rpc
+            .get_minimum_balance_for_rent_exemption(UpgradeableLoaderState::size_of_program())?;
+        // Deprecated in favour of loader-v4, but `initialize_config` and `status`
+        // rely on the upgradeable loader's program data account
+        #[allow(deprecated)]
+        let deploy = bpf_loader_upgradeable::deploy_with_max_program_len(
+            &self.payer.pubkey(),
+            &program.pubkey(),
+            &buffer,
+            &self.payer.pubkey(),
+            lamports,
+            elf.len(),
+        )?;
+        self.send(&deploy, &[program])
+    }
+
+    /// Replace the code of an existing program, extending its data account
+    /// first if the new binary is larger
+    pub fn upgrade(&self, program_id: &Pubkey, elf: &[u8]) -> Result<Signature> {
+        let program_data = get_program_data_address(program_id);
+        let capacity = self
+            .rpc
+            .get_account(&program_data)?
+            .data
+            .len()
+            .saturating_sub(UpgradeableLoaderState::size_of_programdata_metadata());
+        if elf.len() > capacity {
+            let additional_bytes = (elf.len() - capacity) as u32;
+            let extend = bpf_loader_upgradeable::extend_program(
+                program_id,
+                Some(&self.payer.pubkey()),
+                additional_bytes,
+            );
+            self.send(&[extend], &[])?;
+        }
+        let buffer = self.write_buffer(elf)?;
+        let upgrade = bpf_loader_upgradeable::upgrade(
+            program_id,
+            &buffer,
+            &self.payer.pubkey(),
+            &self.payer.pubkey(),
+        );
+        self.send(&[upgrade], &[])
+    }
+}
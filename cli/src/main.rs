@@ -0,0 +1,197 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use serde_json::json;
+use solana_sdk::signature::{read_keypair_file, Signer};
+use test_program_cli::{
+    call::{self, Op},
+    config::{self, Settings},
+    hash,
+    loader::Loader,
+    status, verify, DEFAULT_PROGRAM_KEYPAIR, DEFAULT_PROGRAM_SO,
+};
+use test_program_client::PROGRAM_ID;
+
+#[derive(Parser)]
+#[command(name = "test-program-cli", version, about)]
+struct Cli {
+    /// RPC URL or moniker (localhost, devnet, mainnet-beta); defaults to the Solana CLI config
+    #[arg(short = 'u', long, global = true, env = config::RPC_URL_ENV)]
+    url: Option<String>,
+    /// Signing keypair; defaults to the Solana CLI config
+    #[arg(short = 'k', long, global = true, env = config::KEYPAIR_ENV)]
+    keypair: Option<String>,
+    /// Solana CLI config file
+    #[arg(short = 'C', long, global = true)]
+    config: Option<String>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Deploy a new program from a built binary
+    Deploy {
+        #[arg(long, default_value = DEFAULT_PROGRAM_SO)]
+        program_so: PathBuf,
+        #[arg(long, default_value = DEFAULT_PROGRAM_KEYPAIR)]
+        program_keypair: PathBuf,
+        /// Priority fee in micro-lamports per compute unit
+        #[arg(long, default_value_t = 50_000)]
+        compute_unit_price: u64,
+    },
+    /// Upgrade the deployed program from a built binary
+    Upgrade {
+        #[arg(long, default_value = DEFAULT_PROGRAM_SO)]
+        program_so: PathBuf,
+        #[arg(long, default_value_t = 50_000)]
+        compute_unit_price: u64,
+    },
+    /// Submit the program for verification via the OtterSec API
+    Verify {
+        #[arg(long, env = "REPO_URL", default_value = verify::DEFAULT_REPOSITORY)]
+        repository: String,
+        /// Commit to verify; defaults to the checked out HEAD
+        #[arg(long)]
+        commit: Option<String>,
+        /// Only report the current verification status
+        #[arg(long)]
+        status: bool,
+    },
+    /// Compare the executable hash of a local build with the deployed program
+    VerifyHash {
+        #[arg(long, default_value = DEFAULT_PROGRAM_SO)]
+        program_so: PathBuf,
+        /// Read the deployed binary from a dump file instead of the RPC
+        #[arg(long)]
+        dump: Option<PathBuf>,
+    },
+    /// Show program, upgrade authority and config state
+    Status,
+    /// Simulate an arithmetic instruction and print its result
+    Call {
+        #[arg(value_enum)]
+        op: Op,
+        a: u64,
+        b: u64,
+        /// Also send the transaction instead of only simulating it
+        #[arg(long)]
+        send: bool,
+    },
+}
+
+fn print(value: &impl Serialize) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+fn read_program(path: &Path) -> Result<Vec<u8>> {
+    std::fs::read(path).with_context(|| {
+        format!(
+            "failed to read {}; run `anchor build --verifiable` first",
+            path.display()
+        )
+    })
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let settings = Settings::resolve(
+        cli.url.as_deref(),
+        cli.keypair.as_deref(),
+        cli.config.as_deref(),
+    )?;
+    let rpc_url = config::redact_url(&settings.rpc_url);
+
+    match cli.command {
+        Command::Deploy {
+            program_so,
+            program_keypair,
+            compute_unit_price,
+        } => {
+            let payer = settings.keypair()?;
+            let program = read_keypair_file(&program_keypair).map_err(|err| {
+                anyhow::anyhow!("failed to read {}: {err}", program_keypair.display())
+            })?;
+            let elf = read_program(&program_so)?;
+            let rpc = settings.rpc_client();
+            let loader = Loader {
+                rpc: &rpc,
+                payer: &payer,
+                compute_unit_price: Some(compute_unit_price),
+            };
+            let signature = loader.deploy(&program, &elf)?;
+            print(&json!({
+                "rpc_url": rpc_url,
+                "program_id": program.pubkey().to_string(),
+                "deployer": payer.pubkey().to_string(),
+                "program_size": elf.len(),
+                "signature": signature.to_string(),
+            }))
+        }
+        Command::Upgrade {
+            program_so,
+            compute_unit_price,
+        } => {
+            let payer = settings.keypair()?;
+            let elf = read_program(&program_so)?;
+            let rpc = settings.rpc_client();
+            let loader = Loader {
+                rpc: &rpc,
+                payer: &payer,
+                compute_unit_price: Some(compute_unit_price),
+            };
+            let signature = loader.upgrade(&PROGRAM_ID, &elf)?;
+            print(&json!({
+                "rpc_url": rpc_url,
+                "program_id": PROGRAM_ID.to_string(),
+                "upgrade_authority": payer.pubkey().to_string(),
+                "program_size": elf.len(),
+                "signature": signature.to_string(),
+            }))
+        }
+        Command::Verify {
+            repository,
+            commit,
+            status,
+        } => {
+            if status {
+                return print(&verify::status(&PROGRAM_ID)?);
+            }
+            let commit = match commit {
+                Some(commit) => commit,
+                None => verify::head_commit()?,
+            };
+            print(&verify::submit(&PROGRAM_ID, &repository, &commit)?)
+        }
+        Command::VerifyHash { program_so, dump } => {
+            let local = read_program(&program_so)?;
+            let comparison = match dump {
+                Some(dump) => {
+                    hash::compare(&local, &hash::read_dump(&dump)?, dump.display().to_string())
+                }
+                None => hash::compare(
+                    &local,
+                    &hash::fetch_program(&settings.rpc_client(), &PROGRAM_ID)?,
+                    rpc_url,
+                ),
+            };
+            print(&comparison)?;
+            if !comparison.matches {
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        Command::Status => {
+            let deployer = settings.keypair().ok().map(|keypair| keypair.pubkey());
+            let status = status::fetch(&settings.rpc_client(), &PROGRAM_ID, deployer.as_ref())?;
+            print(&json!({ "rpc_url": rpc_url, "status": status }))
+        }
+        Command::Call { op, a, b, send } => {
+            let payer = settings.keypair()?;
+            print(&call::call(&settings.rpc_client(), &payer, op, a, b, send)?)
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! On-chain state of the program, its upgrade authority and config PDA
+
+use anyhow::Result;
+use serde::Serialize;
+use solana_client::rpc_client::RpcClient;
+use solana_loader_v3_interface::state::UpgradeableLoaderState;
+use solana_sdk::{account::Account, pubkey::Pubkey};
+use solana_sdk_ids::bpf_loader_upgradeable;
+use test_program_client::{anchor_lang::AccountDeserialize, config_address, test_program::Config};
+
+use crate::LAMPORTS_PER_SOL;
+
+#[derive(Debug, Serialize)]
+pub struct Status {
+    pub program_id: String,
+    pub slot: u64,
+    pub deployer: Option<Deployer>,
+    pub program: Option<ProgramStatus>,
+    pub config: Option<ConfigStatus>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Deployer {
+    pub address: String,
+    pub balance_sol: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProgramStatus {
+    pub owner: String,
+    pub executable: bool,
+    pub upgradeable: bool,
+    pub program_data: Option<String>,
+    pub upgrade_authority: Option<String>,
+    pub last_deploy_slot: Option<u64>,
+    pub lamports: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfigStatus {
+    pub address: String,
+    pub admin: String,
+    pub pending_admin: Option<String>,
+    pub paused: bool,
+    pub paused_operations: u32,
+    pub restrict_callers: bool,
+    pub allowed_callers: Vec<String>,
+}
+
+pub fn fetch(rpc: &RpcClient, program_id: &Pubkey, deployer: Option<&Pubkey>) -> Result<Status> {
+    let deployer = match deployer {
+        Some(address) => Some(Deployer {
+            address: address.to_string(),
+            balance_sol: rpc.get_balance(address)? as f64 / LAMPORTS_PER_SOL,
+        }),
+        None => None,
+    };
+    let program = match get_account(rpc, program_id)? {
+        Some(account) => Some(program_status(rpc, &account)?),
+        None => None,
+    };
+    let config = get_account(rpc, &config_address())?
+        .map(|account| config_status(&account))
+        .transpose()?;
+    Ok(Status {
+        program_id: program_id.to_string(),
+        slot: rpc.get_slot()?,
+        deployer,
+        program,
+        config,
+    })
+}
+
+fn get_account(rpc: &RpcClient, address: &Pubkey) -> Result<Option<Account>> {
+    Ok(rpc
+        .get_account_with_commitment(address, rpc.commitment())?
+        .value)
+}
+
+fn program_status(rpc: &RpcClient, account: &Account) -> Result<ProgramStatus> {
+    let mut status = ProgramStatus {
+        owner: account.owner.to_string(),
+        executable: account.executable,
+        upgradeable: account.owner == bpf_loader_upgradeable::ID,
+        program_data: None,
+        upgrade_authority: None,
+        last_deploy_slot: None,
+        lamports: account.lamports,
+    };
+    if !status.upgradeable {
+        return Ok(status);
+    }
+    if let Ok(UpgradeableLoaderState::Program {
+        programdata_address,
+    }) = bincode::deserialize(&account.data)
+    {
+        status.program_data = Some(programdata_address.to_string());
+        if let Some(program_data) = get_account(rpc, &programdata_address)? {
+            if let Ok(UpgradeableLoaderState::ProgramData {
+                slot,
+                upgrade_authority_address,
+            }) = bincode::deserialize(&program_data.data)
+            {
+                status.last_deploy_slot = Some(slot);
+                status.upgrade_authority = upgrade_authority_address.map(|key| key.to_string());
+            }
+        }
+    }
+    Ok(status)
+}
+
+fn config_status(account: &Account) -> Result<ConfigStatus> {
+    let config = Config::try_deserialize(&mut account.data.as_slice())?;
+    Ok(ConfigStatus {
+        address: config_address().to_string(),
+        admin: config.admin.to_string(),
+        pending_admin: config.pending_admin.map(|key| key.to_string()),
+        paused: config.paused,
+        paused_operations: config.paused_operations,
+        restrict_callers: config.restrict_callers,
+        allowed_callers: config
+            .allowed_callers
+            .iter()
+            .map(|key| key.to_string())
+            .collect(),
+    })
+}
@@ -0,0 +1,42 @@
+//! Remote verification through the OtterSec API
+
+use std::process::Command;
+
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+
+pub const OTTERSEC_API: &str = "https://verify.osec.io";
+pub const DEFAULT_REPOSITORY: &str = "https://github.com/uniwexLab/test_program";
+
+/// Ask OtterSec to rebuild `commit_hash` of `repository` and compare it with
+/// the deployed program
+pub fn submit(program_id: &Pubkey, repository: &str, commit_hash: &str) -> Result<Value> {
+    let response = reqwest::blocking::Client::new()
+        .post(format!("{OTTERSEC_API}/verify"))
+        .json(&json!({
+            "repository": repository,
+            "program_id": program_id.to_string(),
+            "commit_hash": commit_hash,
+            "lib_name": "test_program",
+        }))
+        .send()?
+        .error_for_status()?;
+    Ok(response.json()?)
+}
+
+pub fn status(program_id: &Pubkey) -> Result<Value> {
+    let response = reqwest::blocking::get(format!("{OTTERSEC_API}/status/{program_id}"))?
+        .error_for_status()?;
+    Ok(response.json()?)
+}
+
+/// Commit checked out in the current directory
+pub fn head_commit() -> Result<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .context("failed to run git")?;
+    anyhow::ensure!(output.status.success(), "git rev-parse HEAD failed");
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
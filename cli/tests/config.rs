@@ -0,0 +1,64 @@
+use test_program_cli::{
+    call::{decode_result, Op},
+    config::{normalize_url, redact_url, Settings},
+};
+
+#[test]
+fn monikers_expand_to_cluster_urls() {
+    assert_eq!(normalize_url("localhost"), "http://127.0.0.1:8899");
+    assert_eq!(normalize_url("l"), "http://127.0.0.1:8899");
+    assert_eq!(normalize_url("devnet"), "https://api.devnet.solana.com");
+    assert_eq!(normalize_url("https://rpc.example"), "https://rpc.example");
+}
+
+#[test]
+fn api_keys_are_redacted() {
+    assert_eq!(
+        redact_url("https://rpc.example/?api-key=secret"),
+        "https://rpc.example/?<redacted>"
+    );
+    assert_eq!(redact_url("http://127.0.0.1:8899"), "http://127.0.0.1:8899");
+}
+
+#[test]
+fn explicit_settings_override_cli_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("config.yml");
+    std::fs::write(
+        &config_path,
+        "json_rpc_url: https://rpc.from-config\nwebsocket_url: ''\nkeypair_path: /keys/from-config.json\n",
+    )
+    .unwrap();
+    let config_path = config_path.to_str().unwrap();
+
+    let from_file = Settings::resolve(None, None, Some(config_path)).unwrap();
+    assert_eq!(from_file.rpc_url, "https://rpc.from-config");
+    assert_eq!(from_file.keypair_path, "/keys/from-config.json");
+
+    let explicit = Settings::resolve(
+        Some("localhost"),
+        Some("/keys/flag.json"),
+        Some(config_path),
+    )
+    .unwrap();
+    assert_eq!(explicit.rpc_url, "http://127.0.0.1:8899");
+    assert_eq!(explicit.keypair_path, "/keys/flag.json");
+}
+
+#[test]
+fn call_results_decode_by_return_type() {
+    assert_eq!(
+        decode_result(Op::Add, &3u64.to_le_bytes()).unwrap(),
+        serde_json::json!(3)
+    );
+    // Trailing zero bytes are stripped by the runtime
+    assert_eq!(
+        decode_result(Op::DivRem, &[7]).unwrap(),
+        serde_json::json!({ "quotient": 7, "remainder": 0 })
+    );
+    // A zero result leaves no return data at all
+    assert_eq!(
+        decode_result(Op::Subtract, &[]).unwrap(),
+        serde_json::json!(0)
+    );
+}
@@ -0,0 +1,94 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use solana_loader_v3_interface::state::UpgradeableLoaderState;
+use solana_sdk::pubkey::Pubkey;
+use test_program_cli::hash::{compare, executable_hash, read_dump, strip_program_data_header};
+
+fn fake_elf() -> Vec<u8> {
+    let mut elf = b"\x7fELF".to_vec();
+    elf.extend((1..=200u8).cycle().take(4096));
+    elf
+}
+
+/// Program data account contents: loader metadata, the ELF, then zero padding
+/// left over from a larger previous deployment
+fn program_data_account(elf: &[u8]) -> Vec<u8> {
+    let mut data = bincode::serialize(&UpgradeableLoaderState::ProgramData {
+        slot: 42,
+        upgrade_authority_address: Some(Pubkey::new_unique()),
+    })
+    .unwrap();
+    data.resize(UpgradeableLoaderState::size_of_programdata_metadata(), 0);
+    data.extend_from_slice(elf);
+    data.extend(std::iter::repeat_n(0, 1000));
+    data
+}
+
+#[test]
+fn hash_ignores_trailing_zero_padding() {
+    let elf = fake_elf();
+    let mut padded = elf.clone();
+    padded.extend([0; 64]);
+    assert_eq!(executable_hash(&elf), executable_hash(&padded));
+    assert_ne!(
+        executable_hash(&elf),
+        executable_hash(&elf[..elf.len() - 1])
+    );
+    // SHA-256 of the empty input
+    assert_eq!(
+        executable_hash(&[0, 0, 0]),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+}
+
+#[test]
+fn program_data_header_is_stripped() {
+    let elf = fake_elf();
+    let account = program_data_account(&elf);
+    let stripped = strip_program_data_header(&account).unwrap();
+    assert!(stripped.starts_with(&elf));
+    assert_eq!(strip_program_data_header(&elf).unwrap(), elf.as_slice());
+    assert!(strip_program_data_header(b"not a program").is_err());
+}
+
+#[test]
+fn dumps_in_every_format_match_the_local_build() {
+    let elf = fake_elf();
+    let account = program_data_account(&elf);
+    let dir = tempfile::tempdir().unwrap();
+
+    let elf_dump = dir.path().join("program.so");
+    std::fs::write(&elf_dump, &elf).unwrap();
+
+    let raw_dump = dir.path().join("program_data.bin");
+    std::fs::write(&raw_dump, &account).unwrap();
+
+    let json_dump = dir.path().join("program_data.json");
+    let json = serde_json::json!({
+        "pubkey": Pubkey::new_unique().to_string(),
+        "account": {
+            "data": [STANDARD.encode(&account), "base64"],
+            "executable": false,
+            "lamports": 1,
+            "owner": "BPFLoaderUpgradeab1e11111111111111111111111",
+            "rentEpoch": 0,
+        },
+    });
+    std::fs::write(&json_dump, json.to_string()).unwrap();
+
+    for dump in [elf_dump, raw_dump, json_dump] {
+        let comparison = compare(&elf, &read_dump(&dump).unwrap(), String::new());
+        assert!(comparison.matches, "{}", dump.display());
+    }
+}
+
+#[test]
+fn modified_binary_is_reported_as_mismatch() {
+    let elf = fake_elf();
+    let mut patched = elf.clone();
+    patched[100] ^= 1;
+    let account = program_data_account(&patched);
+    let onchain = strip_program_data_header(&account).unwrap();
+    let comparison = compare(&elf, onchain, "dump".to_string());
+    assert!(!comparison.matches);
+    assert_ne!(comparison.local_hash, comparison.onchain_hash);
+}
@@ -0,0 +1,363 @@
+//! Typed helpers for calling `test_program` from off-chain Rust code
+//!
+//! Builders return plain `Instruction`s for the arithmetic instructions, using
+//! the default accounts (the config PDA with no caller or history). Use
+//! [`arithmetic_ix`] directly to pass a caller and history account.
+
+use anchor_lang::{
+    error::ERROR_CODE_OFFSET,
+    prelude::{AccountMeta, Pubkey},
+    solana_program::instruction::{error::InstructionError, Instruction},
+    AnchorDeserialize, InstructionData, ToAccountMetas,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+pub use anchor_lang;
+pub use test_program::{
+    self, instruction as ix, ArithmeticMode, Decimal, DivRem, ErrorCode, Operand, Operation,
+    OperationKind, RoundingMode, Stats, ID as PROGRAM_ID,
+};
+
+/// Signer and optional history account attached to an arithmetic call
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Caller {
+    pub signer: Pubkey,
+    pub history: Option<Pubkey>,
+}
+
+pub fn config_address() -> Pubkey {
+    Pubkey::find_program_address(&[test_program::Config::SEED], &PROGRAM_ID).0
+}
+
+pub fn accumulator_address(authority: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[test_program::Accumulator::SEED, authority.as_ref()],
+        &PROGRAM_ID,
+    )
+    .0
+}
+
+pub fn history_address(authority: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[test_program::History::SEED, authority.as_ref()],
+        &PROGRAM_ID,
+    )
+    .0
+}
+
+fn instruction(data: impl InstructionData, accounts: Vec<AccountMeta>) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts,
+        data: data.data(),
+    }
+}
+
+/// Build any instruction that takes the `Add` accounts
+pub fn arithmetic_ix(data: impl InstructionData, caller: Option<&Caller>) -> Instruction {
+    let accounts = test_program::accounts::Add {
+        config: config_address(),
+        caller: caller.map(|caller| caller.signer),
+        history: caller.and_then(|caller| caller.history),
+    };
+    instruction(data, accounts.to_account_metas(None))
+}
+
+macro_rules! binary_builders {
+    ($($(#[$doc:meta])* $name:ident($ty:ty) => $ix:ident;)*) => {
+        $(
+            $(#[$doc])*
+            pub fn $name(a: $ty, b: $ty) -> Instruction {
+                arithmetic_ix(ix::$ix { a, b }, None)
+            }
+        )*
+    };
+}
+
+binary_builders! {
+    /// Returns `u64`
+    add_ix(u64) => Add;
+    /// Returns `u64`
+    subtract_ix(u64) => Subtract;
+    /// Returns `u64`
+    multiply_ix(u64) => Multiply;
+    /// Returns `u64`
+    max_ix(u64) => Max;
+    /// Returns `u64`
+    min_ix(u64) => Min;
+    /// Returns `u64`
+    abs_diff_ix(u64) => AbsDiff;
+    /// Returns `u64`
+    midpoint_ix(u64) => Midpoint;
+    /// Returns `u64`
+    divide_ix(u64) => Divide;
+    /// Returns `u64`
+    modulo_ix(u64) => Modulo;
+    /// Returns `u64`
+    div_ceil_ix(u64) => DivCeil;
+    /// Returns [`DivRem`]
+    div_rem_ix(u64) => DivRem;
+    /// Returns `u128`
+    add_u128_ix(u128) => AddU128;
+    /// Returns `u128`
+    sub_u128_ix(u128) => SubU128;
+    /// Returns `u128`
+    mul_u128_ix(u128) => MulU128;
+    /// Returns `u128`
+    div_u128_ix(u128) => DivU128;
+    /// Returns `i64`
+    add_i64_ix(i64) => AddI64;
+    /// Returns `i64`
+    sub_i64_ix(i64) => SubI64;
+    /// Returns `i64`
+    mul_i64_ix(i64) => MulI64;
+    /// Returns `i128`
+    add_i128_ix(i128) => AddI128;
+    /// Returns `i128`
+    sub_i128_ix(i128) => SubI128;
+    /// Returns `i128`
+    mul_i128_ix(i128) => MulI128;
+}
+
+macro_rules! mode_builders {
+    ($($name:ident => $ix:ident;)*) => {
+        $(
+            /// Returns `u64`
+            pub fn $name(a: u64, b: u64, mode: ArithmeticMode) -> Instruction {
+                arithmetic_ix(ix::$ix { a, b, mode }, None)
+            }
+        )*
+    };
+}
+
+mode_builders! {
+    add_with_mode_ix => AddWithMode;
+    subtract_with_mode_ix => SubtractWithMode;
+    multiply_with_mode_ix => MultiplyWithMode;
+}
+
+macro_rules! mul_div_builders {
+    ($($name:ident => $ix:ident;)*) => {
+        $(
+            /// Returns `u64`
+            pub fn $name(a: u64, b: u64, c: u64) -> Instruction {
+                arithmetic_ix(ix::$ix { a, b, c }, None)
+            }
+        )*
+    };
+}
+
+mul_div_builders! {
+    mul_div_floor_ix => MulDivFloor;
+    mul_div_ceil_ix => MulDivCeil;
+    mul_div_round_ix => MulDivRound;
+}
+
+/// Returns `u128`
+pub fn mul_div_u128_ix(a: u128, b: u128, c: u128) -> Instruction {
+    arithmetic_ix(ix::MulDivU128 { a, b, c }, None)
+}
+
+/// Returns `u64`
+pub fn clamp_ix(value: u64, lo: u64, hi: u64) -> Instruction {
+    arithmetic_ix(ix::Clamp { value, lo, hi }, None)
+}
+
+/// Returns `u64`
+pub fn isqrt_ix(value: u64) -> Instruction {
+    arithmetic_ix(ix::Isqrt { value }, None)
+}
+
+/// Returns `u64`
+pub fn checked_pow_ix(base: u64, exp: u32) -> Instruction {
+    arithmetic_ix(ix::CheckedPow { base, exp }, None)
+}
+
+/// Returns `u32`
+pub fn ilog2_ix(value: u64) -> Instruction {
+    arithmetic_ix(ix::Ilog2 { value }, None)
+}
+
+/// Returns `u32`
+pub fn ilog10_ix(value: u64) -> Instruction {
+    arithmetic_ix(ix::Ilog10 { value }, None)
+}
+
+/// Returns `u64`
+pub fn nth_root_ix(value: u64, n: u32) -> Instruction {
+    arithmetic_ix(ix::NthRoot { value, n }, None)
+}
+
+macro_rules! decimal_builders {
+    ($($name:ident => $ix:ident;)*) => {
+        $(
+            /// Returns [`Decimal`]
+            pub fn $name(a: Decimal, b: Decimal, scale: u8, rounding: RoundingMode) -> Instruction {
+                arithmetic_ix(ix::$ix { a, b, scale, rounding }, None)
+            }
+        )*
+    };
+}
+
+decimal_builders! {
+    decimal_add_ix => DecimalAdd;
+    decimal_sub_ix => DecimalSub;
+    decimal_mul_ix => DecimalMul;
+    decimal_div_ix => DecimalDiv;
+}
+
+/// Returns `Vec<u64>`
+pub fn execute_batch_ix(operations: Vec<Operation>) -> Instruction {
+    arithmetic_ix(ix::ExecuteBatch { operations }, None)
+}
+
+/// Returns [`Stats`]
+pub fn stats_ix(values: Vec<u64>) -> Instruction {
+    arithmetic_ix(ix::Stats { values }, None)
+}
+
+/// Returns `u64`
+pub fn evaluate_ix(program: Vec<u8>) -> Instruction {
+    arithmetic_ix(ix::Evaluate { program }, None)
+}
+
+pub fn initialize_accumulator_ix(authority: &Pubkey, initial: u64) -> Instruction {
+    let accounts = test_program::accounts::InitializeAccumulator {
+        accumulator: accumulator_address(authority),
+        authority: *authority,
+        system_program: anchor_lang::system_program::ID,
+    };
+    instruction(
+        ix::InitializeAccumulator { initial },
+        accounts.to_account_metas(None),
+    )
+}
+
+/// Build an `accumulate_*` instruction, e.g. `accumulate_ix(&authority, ix::AccumulateAdd { amount })`
+pub fn accumulate_ix(authority: &Pubkey, data: impl InstructionData) -> Instruction {
+    let accounts = test_program::accounts::Accumulate {
+        accumulator: accumulator_address(authority),
+        authority: *authority,
+        config: config_address(),
+    };
+    instruction(data, accounts.to_account_metas(None))
+}
+
+pub fn initialize_history_ix(authority: &Pubkey) -> Instruction {
+    let accounts = test_program::accounts::InitializeHistory {
+        history: history_address(authority),
+        authority: *authority,
+        system_program: anchor_lang::system_program::ID,
+    };
+    instruction(ix::InitializeHistory {}, accounts.to_account_metas(None))
+}
+
+pub fn clear_history_ix(authority: &Pubkey) -> Instruction {
+    let accounts = test_program::accounts::ClearHistory {
+        history: history_address(authority),
+        authority: *authority,
+    };
+    instruction(ix::ClearHistory {}, accounts.to_account_metas(None))
+}
+
+/// Build an admin instruction that takes the `UpdateConfig` accounts,
+/// e.g. `admin_ix(&admin, ix::Pause {})`
+pub fn admin_ix(admin: &Pubkey, data: impl InstructionData) -> Instruction {
+    let accounts = test_program::accounts::UpdateConfig {
+        config: config_address(),
+        admin: *admin,
+    };
+    instruction(data, accounts.to_account_metas(None))
+}
+
+pub fn accept_admin_ix(pending_admin: &Pubkey) -> Instruction {
+    let accounts = test_program::accounts::AcceptAdmin {
+        config: config_address(),
+        pending_admin: *pending_admin,
+    };
+    instruction(ix::AcceptAdmin {}, accounts.to_account_metas(None))
+}
+
+#[derive(Debug)]
+pub enum DecodeError {
+    /// No `Program return:` line for the program was found in the logs
+    MissingReturnData,
+    InvalidBase64(base64::DecodeError),
+    InvalidData(std::io::Error),
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::MissingReturnData => write!(f, "no return data from test_program"),
+            DecodeError::InvalidBase64(err) => write!(f, "invalid base64 return data: {err}"),
+            DecodeError::InvalidData(err) => write!(f, "invalid return data: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Decode return data set by an instruction.
+///
+/// The runtime drops trailing zero bytes from return data, so the input is
+/// zero-padded before deserializing.
+pub fn decode_return_data<T: AnchorDeserialize>(data: &[u8]) -> Result<T, DecodeError> {
+    let mut padded = data.to_vec();
+    padded.resize(data.len() + 1024, 0);
+    T::deserialize(&mut padded.as_slice()).map_err(DecodeError::InvalidData)
+}
+
+/// Find and decode the return data in transaction logs, as returned by
+/// `simulateTransaction` or `getTransaction`
+pub fn decode_return_data_from_logs<T: AnchorDeserialize>(
+    logs: &[String],
+) -> Result<T, DecodeError> {
+    let prefix = format!("Program return: {PROGRAM_ID} ");
+    let encoded = logs
+        .iter()
+        .rev()
+        .find_map(|line| line.strip_prefix(&prefix))
+        .ok_or(DecodeError::MissingReturnData)?;
+    let data = STANDARD
+        .decode(encoded)
+        .map_err(DecodeError::InvalidBase64)?;
+    decode_return_data(&data)
+}
+
+/// Every `ErrorCode` variant, in declaration order
+const ERROR_CODES: &[ErrorCode] = &[
+    ErrorCode::Overflow,
+    ErrorCode::DivideByZero,
+    ErrorCode::Underflow,
+    ErrorCode::InvalidOperand,
+    ErrorCode::Unauthorized,
+    ErrorCode::PrecisionLoss,
+    ErrorCode::BatchTooLarge,
+    ErrorCode::StackUnderflow,
+    ErrorCode::StackOverflow,
+    ErrorCode::ProgramTooLong,
+    ErrorCode::AllowlistFull,
+    ErrorCode::CallerAlreadyAllowed,
+    ErrorCode::CallerNotAllowed,
+    ErrorCode::NoPendingAdmin,
+    ErrorCode::ProgramPaused,
+    ErrorCode::InvalidReturnData,
+    ErrorCode::InvalidRange,
+    ErrorCode::TooManyElements,
+    ErrorCode::BatchOperationFailed,
+];
+
+/// Map a custom program error code back to `ErrorCode`
+pub fn decode_error_code(code: u32) -> Option<ErrorCode> {
+    let index = code.checked_sub(ERROR_CODE_OFFSET)?;
+    ERROR_CODES.get(index as usize).copied()
+}
+
+/// Extract the program's `ErrorCode` from a failed instruction
+pub fn decode_instruction_error(error: &InstructionError) -> Option<ErrorCode> {
+    match error {
+        InstructionError::Custom(code) => decode_error_code(*code),
+        _ => None,
+    }
+}
@@ -0,0 +1,77 @@
+use anchor_lang::{prelude::Pubkey, AnchorSerialize, Discriminator};
+use test_program_client::*;
+
+#[test]
+fn builders_encode_discriminator_and_args() {
+    let ix = add_ix(1, 2);
+    assert_eq!(ix.program_id, PROGRAM_ID);
+    assert_eq!(&ix.data[..8], ix::Add::DISCRIMINATOR);
+    assert_eq!(
+        &ix.data[8..],
+        [1u64.to_le_bytes(), 2u64.to_le_bytes()].concat()
+    );
+    assert_eq!(ix.accounts[0].pubkey, config_address());
+}
+
+#[test]
+fn arithmetic_accounts_fill_optional_placeholders() {
+    // Missing optional accounts are passed as the program id
+    let ix = multiply_ix(3, 4);
+    assert_eq!(ix.accounts.len(), 3);
+    assert_eq!(ix.accounts[1].pubkey, PROGRAM_ID);
+    assert_eq!(ix.accounts[2].pubkey, PROGRAM_ID);
+
+    let caller = Caller {
+        signer: Pubkey::new_unique(),
+        history: Some(Pubkey::new_unique()),
+    };
+    let ix = arithmetic_ix(ix::Multiply { a: 3, b: 4 }, Some(&caller));
+    assert_eq!(ix.accounts[1].pubkey, caller.signer);
+    assert!(ix.accounts[1].is_signer);
+    assert_eq!(ix.accounts[2].pubkey, caller.history.unwrap());
+    assert!(ix.accounts[2].is_writable);
+}
+
+#[test]
+fn return_data_with_trailing_zeros_is_padded() {
+    let value = DivRem {
+        quotient: 7,
+        remainder: 0,
+    };
+    let mut data = value.try_to_vec().unwrap();
+    while data.last() == Some(&0) {
+        data.pop();
+    }
+    assert_eq!(decode_return_data::<DivRem>(&data).unwrap(), value);
+    assert_eq!(decode_return_data::<u64>(&[]).unwrap(), 0);
+}
+
+#[test]
+fn return_data_is_read_from_logs() {
+    let logs = vec![
+        format!("Program {PROGRAM_ID} invoke [1]"),
+        "Program log: Adding 1 + 2 = 3".to_string(),
+        format!("Program return: {PROGRAM_ID} AwAAAAAAAAA="),
+        format!("Program {PROGRAM_ID} success"),
+    ];
+    assert_eq!(decode_return_data_from_logs::<u64>(&logs).unwrap(), 3);
+    assert!(matches!(
+        decode_return_data_from_logs::<u64>(&logs[..2]),
+        Err(DecodeError::MissingReturnData)
+    ));
+}
+
+#[test]
+fn error_codes_round_trip() {
+    for code in 6000..6100 {
+        if let Some(error) = decode_error_code(code) {
+            assert_eq!(u32::from(error), code);
+        }
+    }
+    assert!(matches!(
+        decode_error_code(6002),
+        Some(ErrorCode::Underflow)
+    ));
+    assert!(decode_error_code(42).is_none());
+    assert!(decode_error_code(7000).is_none());
+}
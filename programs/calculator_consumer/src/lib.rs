@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+use test_program::{compose::Calculator, program::TestProgram};
+
+declare_id!("DV96Di29vtR2Wrg8mC4nKFkZp7FNFET6HSYmNFEfinqf");
+
+#[program]
+pub mod calculator_consumer {
+    use super::*;
+
+    /// Compute `(a + b) * c` through two calls into test_program
+    pub fn add_then_multiply(ctx: Context<Compose>, a: u64, b: u64, c: u64) -> Result<u64> {
+        let calculator = ctx.accounts.calculator();
+        let sum = calculator.add(a, b)?;
+        let result = calculator.multiply(sum, c)?;
+        msg!("({} + {}) * {} = {}", a, b, c, result);
+        Ok(result)
+    }
+}
+
+#[derive(Accounts)]
+pub struct Compose<'info> {
+    pub test_program: Program<'info, TestProgram>,
+    /// CHECK: test_program's config PDA, validated by test_program itself
+    pub config: UncheckedAccount<'info>,
+}
+
+impl<'info> Compose<'info> {
+    fn calculator(&self) -> Calculator<'info> {
+        Calculator {
+            program: self.test_program.to_account_info(),
+            config: self.config.to_account_info(),
+        }
+    }
+}
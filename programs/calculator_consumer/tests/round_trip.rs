@@ -0,0 +1,93 @@
+//! Calls test_program through calculator_consumer and checks that results
+//! and errors make it back through return data.
+//!
+//! Both programs must be built first (`anchor build`).
+
+use anchor_lang::{AnchorDeserialize, InstructionData, ToAccountMetas};
+use litesvm::LiteSVM;
+use solana_sdk::{
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+const DEPLOY_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../target/deploy");
+
+fn setup() -> (LiteSVM, Keypair) {
+    let mut svm = LiteSVM::new();
+    svm.add_program_from_file(test_program::ID, format!("{DEPLOY_DIR}/test_program.so"))
+        .unwrap();
+    svm.add_program_from_file(
+        calculator_consumer::ID,
+        format!("{DEPLOY_DIR}/calculator_consumer.so"),
+    )
+    .unwrap();
+    let payer = Keypair::new();
+    svm.airdrop(&payer.pubkey(), 1_000_000_000).unwrap();
+    (svm, payer)
+}
+
+fn add_then_multiply(
+    svm: &mut LiteSVM,
+    payer: &Keypair,
+    a: u64,
+    b: u64,
+    c: u64,
+) -> Result<u64, u32> {
+    let config = Pubkey::find_program_address(&[test_program::Config::SEED], &test_program::ID).0;
+    let instruction = Instruction {
+        program_id: calculator_consumer::ID,
+        accounts: calculator_consumer::accounts::Compose {
+            test_program: test_program::ID,
+            config,
+        }
+        .to_account_metas(None),
+        data: calculator_consumer::instruction::AddThenMultiply { a, b, c }.data(),
+    };
+    // Identical transactions would otherwise be rejected as already processed
+    svm.expire_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[payer],
+        svm.latest_blockhash(),
+    );
+    match svm.send_transaction(tx) {
+        Ok(meta) => {
+            // Trailing zero bytes are dropped from recorded return data
+            let mut data = meta.return_data.data;
+            data.resize(8, 0);
+            assert_eq!(meta.return_data.program_id, calculator_consumer::ID);
+            Ok(u64::try_from_slice(&data).unwrap())
+        }
+        Err(failed) => match failed.err {
+            TransactionError::InstructionError(_, InstructionError::Custom(code)) => Err(code),
+            err => panic!("unexpected error {err:?}: {:#?}", failed.meta.logs),
+        },
+    }
+}
+
+#[test]
+fn results_round_trip_through_cpi() {
+    let (mut svm, payer) = setup();
+    assert_eq!(add_then_multiply(&mut svm, &payer, 2, 3, 4), Ok(20));
+    assert_eq!(add_then_multiply(&mut svm, &payer, 0, 0, 7), Ok(0));
+    assert_eq!(
+        add_then_multiply(&mut svm, &payer, u32::MAX as u64, 1, u32::MAX as u64),
+        Ok((u32::MAX as u64 + 1) * u32::MAX as u64)
+    );
+}
+
+#[test]
+fn callee_errors_propagate() {
+    let (mut svm, payer) = setup();
+    assert_eq!(
+        add_then_multiply(&mut svm, &payer, u64::MAX, 1, 1),
+        Err(test_program::ErrorCode::Overflow.into())
+    );
+    assert_eq!(
+        add_then_multiply(&mut svm, &payer, u64::MAX / 2, 0, 3),
+        Err(test_program::ErrorCode::Overflow.into())
+    );
+}
@@ -0,0 +1,479 @@
+//! Compute unit consumption of every instruction
+//!
+//! Run with `cargo bench -p test_program --bench compute_units` after
+//! `anchor build`. Results are written to `target/compute_units.{json,md}` and
+//! compared against `benches/compute_units.json`; the run fails if any
+//! instruction costs more than `CU_REGRESSION_THRESHOLD` percent (default 5)
+//! above its baseline. A missing baseline is an error; set
+//! `CU_UPDATE_BASELINE=1` to create or rewrite it.
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use std::{collections::BTreeMap, fmt::Write as _, path::Path};
+
+use anchor_lang::InstructionData;
+use common::{arithmetic_ix, instruction, TestContext};
+use solana_sdk::signature::Signer;
+use test_program::{
+    accounts, decimal::Decimal, evaluator::opcode, instruction as ix, stats, ArithmeticMode,
+    Operand, Operation, OperationKind, RoundingMode,
+};
+
+const BASELINE_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/benches/compute_units.json");
+const REPORT_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../target");
+const DEFAULT_THRESHOLD_PERCENT: f64 = 5.0;
+
+fn measure_all() -> BTreeMap<&'static str, u64> {
+    let mut ctx = TestContext::new();
+    let mut results = BTreeMap::new();
+    let mut arithmetic = |ctx: &mut TestContext, name, data: Vec<u8>| {
+        let mut call = arithmetic_ix(ix::Add { a: 0, b: 0 }, None, None);
+        call.data = data;
+        results.insert(name, ctx.measure(call, &[]));
+    };
+    arithmetic(&mut ctx, "add", ix::Add { a: 1, b: 2 }.data());
+    arithmetic(&mut ctx, "subtract", ix::Subtract { a: 5, b: 3 }.data());
+    arithmetic(&mut ctx, "multiply", ix::Multiply { a: 6, b: 7 }.data());
+    // Saturating and wrapping calls overflow on purpose to measure that path
+    for (names, mode, (a, b)) in [
+        (
+            [
+                "add_with_mode_checked",
+                "subtract_with_mode_checked",
+                "multiply_with_mode_checked",
+            ],
+            ArithmeticMode::Checked,
+            (6, 3),
+        ),
+        (
+            [
+                "add_with_mode_saturating",
+                "subtract_with_mode_saturating",
+                "multiply_with_mode_saturating",
+            ],
+            ArithmeticMode::Saturating,
+            (u64::MAX - 1, u64::MAX),
+        ),
+        (
+            [
+                "add_with_mode_wrapping",
+                "subtract_with_mode_wrapping",
+                "multiply_with_mode_wrapping",
+            ],
+            ArithmeticMode::Wrapping,
+            (u64::MAX - 1, u64::MAX),
+        ),
+    ] {
+        let [add, subtract, multiply] = names;
+        arithmetic(&mut ctx, add, ix::AddWithMode { a, b, mode }.data());
+        arithmetic(
+            &mut ctx,
+            subtract,
+            ix::SubtractWithMode { a, b, mode }.data(),
+        );
+        arithmetic(
+            &mut ctx,
+            multiply,
+            ix::MultiplyWithMode { a, b, mode }.data(),
+        );
+    }
+    arithmetic(&mut ctx, "max", ix::Max { a: 6, b: 7 }.data());
+    arithmetic(&mut ctx, "min", ix::Min { a: 6, b: 7 }.data());
+    arithmetic(
+        &mut ctx,
+        "clamp",
+        ix::Clamp {
+            value: 12,
+            lo: 5,
+            hi: 10,
+        }
+        .data(),
+    );
+    arithmetic(&mut ctx, "abs_diff", ix::AbsDiff { a: 6, b: 7 }.data());
+    arithmetic(&mut ctx, "midpoint", ix::Midpoint { a: 6, b: 7 }.data());
+    arithmetic(&mut ctx, "divide", ix::Divide { a: 7, b: 2 }.data());
+    arithmetic(&mut ctx, "modulo", ix::Modulo { a: 7, b: 2 }.data());
+    arithmetic(&mut ctx, "div_ceil", ix::DivCeil { a: 7, b: 2 }.data());
+    arithmetic(&mut ctx, "div_rem", ix::DivRem { a: 7, b: 2 }.data());
+    arithmetic(&mut ctx, "isqrt", ix::Isqrt { value: u64::MAX }.data());
+    arithmetic(
+        &mut ctx,
+        "checked_pow",
+        ix::CheckedPow { base: 3, exp: 40 }.data(),
+    );
+    arithmetic(&mut ctx, "ilog2", ix::Ilog2 { value: u64::MAX }.data());
+    arithmetic(&mut ctx, "ilog10", ix::Ilog10 { value: u64::MAX }.data());
+    arithmetic(
+        &mut ctx,
+        "nth_root",
+        ix::NthRoot {
+            value: u64::MAX,
+            n: 3,
+        }
+        .data(),
+    );
+    arithmetic(
+        &mut ctx,
+        "add_u128",
+        ix::AddU128 {
+            a: u64::MAX as u128,
+            b: 1,
+        }
+        .data(),
+    );
+    arithmetic(&mut ctx, "sub_u128", ix::SubU128 { a: 5, b: 3 }.data());
+    arithmetic(
+        &mut ctx,
+        "mul_u128",
+        ix::MulU128 {
+            a: u64::MAX as u128,
+            b: 3,
+        }
+        .data(),
+    );
+    arithmetic(
+        &mut ctx,
+        "div_u128",
+        ix::DivU128 { a: u128::MAX, b: 3 }.data(),
+    );
+    arithmetic(
+        &mut ctx,
+        "mul_div_u128",
+        ix::MulDivU128 {
+            a: u128::MAX,
+            b: 3,
+            c: 4,
+        }
+        .data(),
+    );
+    for (name, data) in [
+        (
+            "mul_div_floor",
+            ix::MulDivFloor {
+                a: u64::MAX,
+                b: 7,
+                c: 9,
+            }
+            .data(),
+        ),
+        (
+            "mul_div_ceil",
+            ix::MulDivCeil {
+                a: u64::MAX,
+                b: 7,
+                c: 9,
+            }
+            .data(),
+        ),
+        (
+            "mul_div_round",
+            ix::MulDivRound {
+                a: u64::MAX,
+                b: 7,
+                c: 9,
+            }
+            .data(),
+        ),
+    ] {
+        arithmetic(&mut ctx, name, data);
+    }
+    arithmetic(&mut ctx, "add_i64", ix::AddI64 { a: -5, b: 3 }.data());
+    arithmetic(&mut ctx, "sub_i64", ix::SubI64 { a: -5, b: 3 }.data());
+    arithmetic(&mut ctx, "mul_i64", ix::MulI64 { a: -5, b: 3 }.data());
+    arithmetic(&mut ctx, "add_i128", ix::AddI128 { a: -5, b: 3 }.data());
+    arithmetic(&mut ctx, "sub_i128", ix::SubI128 { a: -5, b: 3 }.data());
+    arithmetic(&mut ctx, "mul_i128", ix::MulI128 { a: -5, b: 3 }.data());
+
+    let (a, b) = (
+        Decimal {
+            mantissa: 2_500_000,
+            scale: 6,
+        },
+        Decimal {
+            mantissa: 3,
+            scale: 0,
+        },
+    );
+    let rounding = RoundingMode::HalfEven;
+    arithmetic(
+        &mut ctx,
+        "decimal_add",
+        ix::DecimalAdd {
+            a,
+            b,
+            scale: 6,
+            rounding,
+        }
+        .data(),
+    );
+    arithmetic(
+        &mut ctx,
+        "decimal_sub",
+        ix::DecimalSub {
+            a: b,
+            b: a,
+            scale: 6,
+            rounding,
+        }
+        .data(),
+    );
+    arithmetic(
+        &mut ctx,
+        "decimal_mul",
+        ix::DecimalMul {
+            a,
+            b,
+            scale: 6,
+            rounding,
+        }
+        .data(),
+    );
+    arithmetic(
+        &mut ctx,
+        "decimal_div",
+        ix::DecimalDiv {
+            a,
+            b,
+            scale: 6,
+            rounding,
+        }
+        .data(),
+    );
+
+    let operations = vec![
+        Operation::Add(Operand::Value(2), Operand::Value(3)),
+        Operation::Multiply(Operand::Result(0), Operand::Value(4)),
+        Operation::Max(Operand::Result(1), Operand::Value(10)),
+    ];
+    arithmetic(
+        &mut ctx,
+        "execute_batch",
+        ix::ExecuteBatch { operations }.data(),
+    );
+    let values = (0..stats::MAX_STATS_LEN as u64).rev().collect();
+    arithmetic(&mut ctx, "stats", ix::Stats { values }.data());
+    let mut program = Vec::new();
+    for value in [2u64, 3] {
+        program.push(opcode::PUSH);
+        program.extend_from_slice(&value.to_le_bytes());
+    }
+    program.push(opcode::MUL);
+    arithmetic(&mut ctx, "evaluate", ix::Evaluate { program }.data());
+
+    let admin = ctx.payer.insecure_clone();
+    let user = ctx.new_user();
+    let update_config = |data: Vec<u8>| {
+        let mut call = instruction(
+            ix::Pause {},
+            accounts::UpdateConfig {
+                config: common::config_address(),
+                admin: admin.pubkey(),
+            },
+        );
+        call.data = data;
+        call
+    };
+    let config_accounts = accounts::InitializeConfig {
+        config: common::config_address(),
+        admin: admin.pubkey(),
+        program: test_program::ID,
+        program_data: common::program_data_address(),
+        system_program: solana_sdk::system_program::ID,
+    };
+    let cu = ctx.measure(instruction(ix::InitializeConfig {}, config_accounts), &[]);
+    results.insert("initialize_config", cu);
+    let cu = ctx.measure(update_config(ix::Pause {}.data()), &[]);
+    results.insert("pause", cu);
+    let cu = ctx.measure(update_config(ix::Unpause {}.data()), &[]);
+    results.insert("unpause", cu);
+    let data = ix::SetOperationPaused {
+        operation: OperationKind::Max,
+        paused: false,
+    }
+    .data();
+    let cu = ctx.measure(update_config(data), &[]);
+    results.insert("set_operation_paused", cu);
+    let cu = ctx.measure(
+        update_config(
+            ix::AddCaller {
+                caller: user.pubkey(),
+            }
+            .data(),
+        ),
+        &[],
+    );
+    results.insert("add_caller", cu);
+    let cu = ctx.measure(
+        update_config(
+            ix::RemoveCaller {
+                caller: user.pubkey(),
+            }
+            .data(),
+        ),
+        &[],
+    );
+    results.insert("remove_caller", cu);
+    let cu = ctx.measure(
+        update_config(ix::SetCallerRestriction { enabled: false }.data()),
+        &[],
+    );
+    results.insert("set_caller_restriction", cu);
+    let cu = ctx.measure(
+        update_config(
+            ix::ProposeAdmin {
+                new_admin: user.pubkey(),
+            }
+            .data(),
+        ),
+        &[],
+    );
+    results.insert("propose_admin", cu);
+    let cu = ctx.measure(update_config(ix::CancelAdminTransfer {}.data()), &[]);
+    results.insert("cancel_admin_transfer", cu);
+    ctx.measure(
+        update_config(
+            ix::ProposeAdmin {
+                new_admin: user.pubkey(),
+            }
+            .data(),
+        ),
+        &[],
+    );
+    let accept = instruction(
+        ix::AcceptAdmin {},
+        accounts::AcceptAdmin {
+            config: common::config_address(),
+            pending_admin: user.pubkey(),
+        },
+    );
+    results.insert("accept_admin", ctx.measure(accept, &[&user]));
+    // Arithmetic cost with an initialized config to load
+    let add = arithmetic_ix(ix::Add { a: 1, b: 2 }, None, None);
+    results.insert("add_with_config", ctx.measure(add, &[]));
+
+    let history = common::history_address(&user.pubkey());
+    let init = instruction(
+        ix::InitializeHistory {},
+        accounts::InitializeHistory {
+            history,
+            authority: user.pubkey(),
+            system_program: solana_sdk::system_program::ID,
+        },
+    );
+    results.insert("initialize_history", ctx.measure(init, &[&user]));
+    let add = arithmetic_ix(ix::Add { a: 1, b: 2 }, Some(user.pubkey()), Some(history));
+    results.insert("add_with_history", ctx.measure(add, &[&user]));
+    let clear = instruction(
+        ix::ClearHistory {},
+        accounts::ClearHistory {
+            history,
+            authority: user.pubkey(),
+        },
+    );
+    results.insert("clear_history", ctx.measure(clear, &[&user]));
+
+    let accumulator = common::accumulator_address(&user.pubkey());
+    let init = instruction(
+        ix::InitializeAccumulator { initial: 10 },
+        accounts::InitializeAccumulator {
+            accumulator,
+            authority: user.pubkey(),
+            system_program: solana_sdk::system_program::ID,
+        },
+    );
+    results.insert("initialize_accumulator", ctx.measure(init, &[&user]));
+    let accumulate = |data: Vec<u8>| {
+        let mut call = instruction(
+            ix::AccumulateAdd { amount: 0 },
+            accounts::Accumulate {
+                accumulator,
+                authority: user.pubkey(),
+                config: common::config_address(),
+            },
+        );
+        call.data = data;
+        call
+    };
+    let cu = ctx.measure(accumulate(ix::AccumulateAdd { amount: 5 }.data()), &[&user]);
+    results.insert("accumulate_add", cu);
+    let cu = ctx.measure(accumulate(ix::AccumulateSub { amount: 5 }.data()), &[&user]);
+    results.insert("accumulate_sub", cu);
+    let cu = ctx.measure(accumulate(ix::AccumulateMul { amount: 5 }.data()), &[&user]);
+    results.insert("accumulate_mul", cu);
+    let cu = ctx.measure(accumulate(ix::AccumulateMax { amount: 5 }.data()), &[&user]);
+    results.insert("accumulate_max", cu);
+
+    results
+}
+
+fn markdown_report(results: &BTreeMap<&str, u64>, baseline: &BTreeMap<String, u64>) -> String {
+    let mut report =
+        String::from("| Instruction | CU | Baseline | Change |\n|---|---:|---:|---:|\n");
+    for (name, cu) in results {
+        let (base, change) = match baseline.get(*name) {
+            Some(base) => (
+                base.to_string(),
+                format!("{:+.1}%", percent_change(*base, *cu)),
+            ),
+            None => ("-".to_string(), "new".to_string()),
+        };
+        writeln!(report, "| `{name}` | {cu} | {base} | {change} |").unwrap();
+    }
+    report
+}
+
+fn percent_change(base: u64, current: u64) -> f64 {
+    (current as f64 - base as f64) / base.max(1) as f64 * 100.0
+}
+
+fn main() {
+    let update_baseline = std::env::var_os("CU_UPDATE_BASELINE").is_some();
+    let baseline: BTreeMap<String, u64> = match std::fs::read_to_string(BASELINE_PATH) {
+        Ok(contents) => serde_json::from_str(&contents).expect("invalid baseline file"),
+        Err(_) if update_baseline => BTreeMap::new(),
+        Err(err) => {
+            eprintln!("Cannot read baseline {BASELINE_PATH}: {err}");
+            eprintln!("Run with CU_UPDATE_BASELINE=1 to create it");
+            std::process::exit(1);
+        }
+    };
+    let results = measure_all();
+
+    let json = serde_json::to_string_pretty(&results).unwrap() + "\n";
+    let report = markdown_report(&results, &baseline);
+    std::fs::create_dir_all(REPORT_DIR).unwrap();
+    std::fs::write(Path::new(REPORT_DIR).join("compute_units.json"), &json).unwrap();
+    std::fs::write(Path::new(REPORT_DIR).join("compute_units.md"), &report).unwrap();
+    print!("{report}");
+
+    if update_baseline {
+        std::fs::write(BASELINE_PATH, &json).unwrap();
+        println!("Wrote baseline to {BASELINE_PATH}");
+        return;
+    }
+
+    let threshold = std::env::var("CU_REGRESSION_THRESHOLD")
+        .ok()
+        .map(|value| {
+            value
+                .parse::<f64>()
+                .expect("CU_REGRESSION_THRESHOLD must be a number")
+        })
+        .unwrap_or(DEFAULT_THRESHOLD_PERCENT);
+    let regressions: Vec<String> = results
+        .iter()
+        .filter_map(|(name, cu)| {
+            let base = *baseline.get(*name)?;
+            let change = percent_change(base, *cu);
+            (change > threshold).then(|| format!("{name}: {base} -> {cu} CU ({change:+.1}%)"))
+        })
+        .collect();
+    if !regressions.is_empty() {
+        eprintln!("Compute unit regressions above {threshold}%:");
+        for regression in &regressions {
+            eprintln!("  {regression}");
+        }
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,112 @@
+//! Feeds arbitrary argument bytes to every instruction through the Anchor
+//! dispatcher. Malformed input must surface as a `ProgramError`, never a panic.
+
+#![no_main]
+
+use anchor_lang::{prelude::*, Discriminator};
+use libfuzzer_sys::fuzz_target;
+use test_program::{instruction as ix, Config};
+
+const DISCRIMINATORS: &[&[u8]] = &[
+    ix::Add::DISCRIMINATOR,
+    ix::Subtract::DISCRIMINATOR,
+    ix::Multiply::DISCRIMINATOR,
+    ix::AddWithMode::DISCRIMINATOR,
+    ix::SubtractWithMode::DISCRIMINATOR,
+    ix::MultiplyWithMode::DISCRIMINATOR,
+    ix::Max::DISCRIMINATOR,
+    ix::Min::DISCRIMINATOR,
+    ix::Clamp::DISCRIMINATOR,
+    ix::AbsDiff::DISCRIMINATOR,
+    ix::Midpoint::DISCRIMINATOR,
+    ix::Divide::DISCRIMINATOR,
+    ix::Modulo::DISCRIMINATOR,
+    ix::DivCeil::DISCRIMINATOR,
+    ix::DivRem::DISCRIMINATOR,
+    ix::Isqrt::DISCRIMINATOR,
+    ix::CheckedPow::DISCRIMINATOR,
+    ix::Ilog2::DISCRIMINATOR,
+    ix::Ilog10::DISCRIMINATOR,
+    ix::NthRoot::DISCRIMINATOR,
+    ix::AddU128::DISCRIMINATOR,
+    ix::SubU128::DISCRIMINATOR,
+    ix::MulU128::DISCRIMINATOR,
+    ix::DivU128::DISCRIMINATOR,
+    ix::MulDivU128::DISCRIMINATOR,
+    ix::MulDivFloor::DISCRIMINATOR,
+    ix::MulDivCeil::DISCRIMINATOR,
+    ix::MulDivRound::DISCRIMINATOR,
+    ix::AddI64::DISCRIMINATOR,
+    ix::SubI64::DISCRIMINATOR,
+    ix::MulI64::DISCRIMINATOR,
+    ix::AddI128::DISCRIMINATOR,
+    ix::SubI128::DISCRIMINATOR,
+    ix::MulI128::DISCRIMINATOR,
+    ix::DecimalAdd::DISCRIMINATOR,
+    ix::DecimalSub::DISCRIMINATOR,
+    ix::DecimalMul::DISCRIMINATOR,
+    ix::DecimalDiv::DISCRIMINATOR,
+    ix::ExecuteBatch::DISCRIMINATOR,
+    ix::Stats::DISCRIMINATOR,
+    ix::Evaluate::DISCRIMINATOR,
+    ix::InitializeConfig::DISCRIMINATOR,
+    ix::ProposeAdmin::DISCRIMINATOR,
+    ix::AcceptAdmin::DISCRIMINATOR,
+    ix::CancelAdminTransfer::DISCRIMINATOR,
+    ix::AddCaller::DISCRIMINATOR,
+    ix::RemoveCaller::DISCRIMINATOR,
+    ix::SetCallerRestriction::DISCRIMINATOR,
+    ix::Pause::DISCRIMINATOR,
+    ix::Unpause::DISCRIMINATOR,
+    ix::SetOperationPaused::DISCRIMINATOR,
+    ix::InitializeHistory::DISCRIMINATOR,
+    ix::ClearHistory::DISCRIMINATOR,
+    ix::InitializeAccumulator::DISCRIMINATOR,
+    ix::AccumulateAdd::DISCRIMINATOR,
+    ix::AccumulateSub::DISCRIMINATOR,
+    ix::AccumulateMul::DISCRIMINATOR,
+    ix::AccumulateMax::DISCRIMINATOR,
+];
+
+fuzz_target!(|input: &[u8]| {
+    let Some((&selector, args)) = input.split_first() else {
+        return;
+    };
+    let discriminator = DISCRIMINATORS[selector as usize % DISCRIMINATORS.len()];
+    let data = [discriminator, args].concat();
+
+    let program_id = test_program::ID;
+    let system_program = anchor_lang::system_program::ID;
+    let config_key = Pubkey::find_program_address(&[Config::SEED], &program_id).0;
+    let (mut config_lamports, mut config_data) = (0u64, Vec::new());
+    let (mut program_lamports, mut program_data) = (1u64, Vec::new());
+
+    // An empty config PDA followed by the program id, which Anchor reads as
+    // "not provided" for the optional caller and history accounts.
+    let program = AccountInfo::new(
+        &program_id,
+        false,
+        false,
+        &mut program_lamports,
+        &mut program_data,
+        &program_id,
+        true,
+        0,
+    );
+    let accounts = [
+        AccountInfo::new(
+            &config_key,
+            false,
+            false,
+            &mut config_lamports,
+            &mut config_data,
+            &system_program,
+            false,
+            0,
+        ),
+        program.clone(),
+        program,
+    ];
+
+    let _ = test_program::entry(&program_id, &accounts, &data);
+});
@@ -0,0 +1,86 @@
+//! Evaluation of several u64 operations in a single instruction
+
+use anchor_lang::prelude::*;
+
+use crate::ErrorCode;
+
+/// Upper bound on the number of operations accepted by `execute_batch`
+pub const MAX_BATCH_LEN: usize = 64;
+
+/// Input to a batched operation
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operand {
+    /// A literal value
+    Value(u64),
+    /// The result of an earlier operation in the same batch
+    Result(u16),
+}
+
+/// A single step of a batch
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation {
+    Add(Operand, Operand),
+    Subtract(Operand, Operand),
+    Multiply(Operand, Operand),
+    Max(Operand, Operand),
+    Divide(Operand, Operand),
+    Modulo(Operand, Operand),
+}
+
+/// Evaluate `operations` in order, stopping at the first failure.
+///
+/// A failing operation is reported as `BatchOperationFailed` with its index
+/// as the error value; the underlying error is logged before it.
+pub fn execute(operations: &[Operation]) -> Result<Vec<u64>> {
+    if operations.len() > MAX_BATCH_LEN {
+        return Err(ErrorCode::BatchTooLarge.with_operands(operations.len(), MAX_BATCH_LEN));
+    }
+    let mut results = Vec::with_capacity(operations.len());
+    for (index, operation) in operations.iter().enumerate() {
+        let result = apply(operation, &results).map_err(|error| {
+            error.log();
+            msg!("Batch operation {} failed", index);
+            Error::from(ErrorCode::BatchOperationFailed).with_values(("index", index))
+        })?;
+        results.push(result);
+    }
+    Ok(results)
+}
+
+fn apply(operation: &Operation, results: &[u64]) -> Result<u64> {
+    let resolve = |operand: &Operand| match *operand {
+        Operand::Value(value) => Ok(value),
+        Operand::Result(index) => results
+            .get(index as usize)
+            .copied()
+            .ok_or_else(|| ErrorCode::InvalidOperand.with_operands(index, results.len())),
+    };
+    match operation {
+        Operation::Add(a, b) => {
+            let (a, b) = (resolve(a)?, resolve(b)?);
+            a.checked_add(b)
+                .ok_or_else(|| ErrorCode::Overflow.with_operands(a, b))
+        }
+        Operation::Subtract(a, b) => {
+            let (a, b) = (resolve(a)?, resolve(b)?);
+            a.checked_sub(b)
+                .ok_or_else(|| ErrorCode::Underflow.with_operands(a, b))
+        }
+        Operation::Multiply(a, b) => {
+            let (a, b) = (resolve(a)?, resolve(b)?);
+            a.checked_mul(b)
+                .ok_or_else(|| ErrorCode::Overflow.with_operands(a, b))
+        }
+        Operation::Max(a, b) => Ok(resolve(a)?.max(resolve(b)?)),
+        Operation::Divide(a, b) => {
+            let (a, b) = (resolve(a)?, resolve(b)?);
+            a.checked_div(b)
+                .ok_or_else(|| ErrorCode::DivideByZero.with_operands(a, b))
+        }
+        Operation::Modulo(a, b) => {
+            let (a, b) = (resolve(a)?, resolve(b)?);
+            a.checked_rem(b)
+                .ok_or_else(|| ErrorCode::DivideByZero.with_operands(a, b))
+        }
+    }
+}
@@ -0,0 +1,180 @@
+//! Helpers for calling this program from other programs
+//!
+//! Wraps the Anchor-generated `cpi` functions so that each call returns its
+//! typed result, read back from the return data this program sets. Enable the
+//! `cpi` feature to use it.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::get_return_data;
+
+use crate::{cpi, ArithmeticMode, Decimal, DivRem, ErrorCode, Operation, RoundingMode, Stats};
+
+/// Accounts needed to call the arithmetic instructions
+#[derive(Clone)]
+pub struct Calculator<'info> {
+    /// This program's executable account
+    pub program: AccountInfo<'info>,
+    /// The config PDA at `[Config::SEED]`, which may be uninitialized
+    pub config: AccountInfo<'info>,
+}
+
+/// Decode the return data left by the last call into this program
+pub fn read_return_data<T: AnchorDeserialize>() -> Result<T> {
+    let (program_id, data) = get_return_data().ok_or(ErrorCode::InvalidReturnData)?;
+    require_keys_eq!(program_id, crate::ID, ErrorCode::InvalidReturnData);
+    T::try_from_slice(&data).map_err(|_| error!(ErrorCode::InvalidReturnData))
+}
+
+macro_rules! binary_calls {
+    ($($name:ident($ty:ty) -> $ret:ty;)*) => {
+        $(
+            pub fn $name(&self, a: $ty, b: $ty) -> Result<$ret> {
+                cpi::$name(self.context(), a, b)?;
+                read_return_data()
+            }
+        )*
+    };
+}
+
+macro_rules! mode_calls {
+    ($($name:ident;)*) => {
+        $(
+            pub fn $name(&self, a: u64, b: u64, mode: ArithmeticMode) -> Result<u64> {
+                cpi::$name(self.context(), a, b, mode)?;
+                read_return_data()
+            }
+        )*
+    };
+}
+
+macro_rules! decimal_calls {
+    ($($name:ident;)*) => {
+        $(
+            pub fn $name(
+                &self,
+                a: Decimal,
+                b: Decimal,
+                scale: u8,
+                rounding: RoundingMode,
+            ) -> Result<Decimal> {
+                cpi::$name(self.context(), a, b, scale, rounding)?;
+                read_return_data()
+            }
+        )*
+    };
+}
+
+impl<'info> Calculator<'info> {
+    fn context(&self) -> CpiContext<'_, '_, '_, 'info, cpi::accounts::Add<'info>> {
+        CpiContext::new(
+            self.program.clone(),
+            cpi::accounts::Add {
+                config: self.config.clone(),
+                caller: None,
+                history: None,
+            },
+        )
+    }
+
+    binary_calls! {
+        add(u64) -> u64;
+        subtract(u64) -> u64;
+        multiply(u64) -> u64;
+        max(u64) -> u64;
+        min(u64) -> u64;
+        abs_diff(u64) -> u64;
+        midpoint(u64) -> u64;
+        divide(u64) -> u64;
+        modulo(u64) -> u64;
+        div_ceil(u64) -> u64;
+        div_rem(u64) -> DivRem;
+        add_u128(u128) -> u128;
+        sub_u128(u128) -> u128;
+        mul_u128(u128) -> u128;
+        div_u128(u128) -> u128;
+        add_i64(i64) -> i64;
+        sub_i64(i64) -> i64;
+        mul_i64(i64) -> i64;
+        add_i128(i128) -> i128;
+        sub_i128(i128) -> i128;
+        mul_i128(i128) -> i128;
+    }
+
+    mode_calls! {
+        add_with_mode;
+        subtract_with_mode;
+        multiply_with_mode;
+    }
+
+    decimal_calls! {
+        decimal_add;
+        decimal_sub;
+        decimal_mul;
+        decimal_div;
+    }
+
+    pub fn mul_div_floor(&self, a: u64, b: u64, c: u64) -> Result<u64> {
+        cpi::mul_div_floor(self.context(), a, b, c)?;
+        read_return_data()
+    }
+
+    pub fn mul_div_ceil(&self, a: u64, b: u64, c: u64) -> Result<u64> {
+        cpi::mul_div_ceil(self.context(), a, b, c)?;
+        read_return_data()
+    }
+
+    pub fn mul_div_round(&self, a: u64, b: u64, c: u64) -> Result<u64> {
+        cpi::mul_div_round(self.context(), a, b, c)?;
+        read_return_data()
+    }
+
+    pub fn mul_div_u128(&self, a: u128, b: u128, c: u128) -> Result<u128> {
+        cpi::mul_div_u128(self.context(), a, b, c)?;
+        read_return_data()
+    }
+
+    pub fn clamp(&self, value: u64, lo: u64, hi: u64) -> Result<u64> {
+        cpi::clamp(self.context(), value, lo, hi)?;
+        read_return_data()
+    }
+
+    pub fn isqrt(&self, value: u64) -> Result<u64> {
+        cpi::isqrt(self.context(), value)?;
+        read_return_data()
+    }
+
+    pub fn checked_pow(&self, base: u64, exp: u32) -> Result<u64> {
+        cpi::checked_pow(self.context(), base, exp)?;
+        read_return_data()
+    }
+
+    pub fn ilog2(&self, value: u64) -> Result<u32> {
+        cpi::ilog2(self.context(), value)?;
+        read_return_data()
+    }
+
+    pub fn ilog10(&self, value: u64) -> Result<u32> {
+        cpi::ilog10(self.context(), value)?;
+        read_return_data()
+    }
+
+    pub fn nth_root(&self, value: u64, n: u32) -> Result<u64> {
+        cpi::nth_root(self.context(), value, n)?;
+        read_return_data()
+    }
+
+    pub fn execute_batch(&self, operations: Vec<Operation>) -> Result<Vec<u64>> {
+        cpi::execute_batch(self.context(), operations)?;
+        read_return_data()
+    }
+
+    pub fn stats(&self, values: Vec<u64>) -> Result<Stats> {
+        cpi::stats(self.context(), values)?;
+        read_return_data()
+    }
+
+    pub fn evaluate(&self, program: Vec<u8>) -> Result<u64> {
+        cpi::evaluate(self.context(), program)?;
+        read_return_data()
+    }
+}
@@ -0,0 +1,196 @@
+//! Fixed-point decimal numbers with an explicit base-10 scale
+
+use anchor_lang::prelude::*;
+use std::fmt;
+
+use crate::math::{div_wide_u128, full_mul_u128};
+use crate::ErrorCode;
+
+/// Largest scale accepted for operands and results
+pub const MAX_SCALE: u8 = 18;
+
+/// A decimal value equal to `mantissa / 10^scale`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Decimal {
+    pub mantissa: u128,
+    pub scale: u8,
+}
+
+/// How to round when a result has more digits than the requested scale
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// Round toward zero
+    Floor,
+    /// Round away from zero
+    Ceil,
+    /// Round to nearest, ties to the even neighbour
+    HalfEven,
+    /// Fail with `PrecisionLoss` instead of rounding
+    Strict,
+}
+
+impl Decimal {
+    pub fn new(mantissa: u128, scale: u8) -> Result<Self> {
+        check_scale(scale)?;
+        Ok(Self { mantissa, scale })
+    }
+
+    /// Convert to another scale, rounding if digits have to be dropped
+    pub fn rescale(self, scale: u8, rounding: RoundingMode) -> Result<Self> {
+        check_scale(self.scale)?;
+        check_scale(scale)?;
+        if scale >= self.scale {
+            let factor = pow10(scale - self.scale);
+            let mantissa = self
+                .mantissa
+                .checked_mul(factor)
+                .ok_or_else(|| ErrorCode::Overflow.with_operands(self, scale))?;
+            return Ok(Self { mantissa, scale });
+        }
+        let divisor = pow10(self.scale - scale);
+        let mantissa = round_quotient(
+            self.mantissa / divisor,
+            self.mantissa % divisor,
+            (0, divisor),
+            rounding,
+        )?;
+        Ok(Self { mantissa, scale })
+    }
+
+    pub fn checked_add(self, other: Self, scale: u8, rounding: RoundingMode) -> Result<Self> {
+        let common = self.scale.max(other.scale);
+        let a = self.rescale(common, RoundingMode::Strict)?;
+        let b = other.rescale(common, RoundingMode::Strict)?;
+        let mantissa = a
+            .mantissa
+            .checked_add(b.mantissa)
+            .ok_or_else(|| ErrorCode::Overflow.with_operands(self, other))?;
+        Self {
+            mantissa,
+            scale: common,
+        }
+        .rescale(scale, rounding)
+    }
+
+    pub fn checked_sub(self, other: Self, scale: u8, rounding: RoundingMode) -> Result<Self> {
+        let common = self.scale.max(other.scale);
+        let a = self.rescale(common, RoundingMode::Strict)?;
+        let b = other.rescale(common, RoundingMode::Strict)?;
+        let mantissa = a
+            .mantissa
+            .checked_sub(b.mantissa)
+            .ok_or_else(|| ErrorCode::Underflow.with_operands(self, other))?;
+        Self {
+            mantissa,
+            scale: common,
+        }
+        .rescale(scale, rounding)
+    }
+
+    pub fn checked_mul(self, other: Self, scale: u8, rounding: RoundingMode) -> Result<Self> {
+        check_scale(self.scale)?;
+        check_scale(other.scale)?;
+        check_scale(scale)?;
+        let (high, low) = full_mul_u128(self.mantissa, other.mantissa);
+        let product_scale = self.scale + other.scale;
+        if scale >= product_scale {
+            if high != 0 {
+                return Err(ErrorCode::Overflow.with_operands(self, other));
+            }
+            let factor = pow10(scale - product_scale);
+            let mantissa = low
+                .checked_mul(factor)
+                .ok_or_else(|| ErrorCode::Overflow.with_operands(self, other))?;
+            return Ok(Self { mantissa, scale });
+        }
+        let divisor = pow10(product_scale - scale);
+        let (quotient, remainder) = div_wide_u128(high, low, divisor)
+            .ok_or_else(|| ErrorCode::Overflow.with_operands(self, other))?;
+        let mantissa = round_quotient(quotient, remainder, (0, divisor), rounding)?;
+        Ok(Self { mantissa, scale })
+    }
+
+    pub fn checked_div(self, other: Self, scale: u8, rounding: RoundingMode) -> Result<Self> {
+        check_scale(self.scale)?;
+        check_scale(other.scale)?;
+        check_scale(scale)?;
+        if other.mantissa == 0 {
+            return Err(ErrorCode::DivideByZero.with_operands(self, other));
+        }
+        // result = a.mantissa * 10^(scale + b.scale - a.scale) / b.mantissa
+        let shift = scale as i16 + other.scale as i16 - self.scale as i16;
+        let mantissa = if shift >= 0 {
+            let (high, low) = full_mul_u128(self.mantissa, pow10(shift as u8));
+            let (quotient, remainder) = div_wide_u128(high, low, other.mantissa)
+                .ok_or_else(|| ErrorCode::Overflow.with_operands(self, other))?;
+            round_quotient(quotient, remainder, (0, other.mantissa), rounding)?
+        } else {
+            let divisor = full_mul_u128(other.mantissa, pow10((-shift) as u8));
+            match divisor {
+                (0, low) => {
+                    round_quotient(self.mantissa / low, self.mantissa % low, divisor, rounding)?
+                }
+                _ => round_quotient(0, self.mantissa, divisor, rounding)?,
+            }
+        };
+        Ok(Self { mantissa, scale })
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.scale == 0 || self.scale > MAX_SCALE {
+            return write!(f, "{}e-{}", self.mantissa, self.scale);
+        }
+        let factor = pow10(self.scale);
+        write!(
+            f,
+            "{}.{:0width$}",
+            self.mantissa / factor,
+            self.mantissa % factor,
+            width = self.scale as usize
+        )
+    }
+}
+
+fn check_scale(scale: u8) -> Result<()> {
+    if scale > MAX_SCALE {
+        return Err(ErrorCode::InvalidOperand.with_operands(scale, MAX_SCALE));
+    }
+    Ok(())
+}
+
+fn pow10(exp: u8) -> u128 {
+    10u128.pow(exp as u32)
+}
+
+/// Apply `rounding` to a truncated quotient given its remainder and the
+/// (possibly 256-bit, as high/low halves) divisor it came from.
+pub(crate) fn round_quotient(
+    quotient: u128,
+    remainder: u128,
+    divisor: (u128, u128),
+    rounding: RoundingMode,
+) -> Result<u128> {
+    if remainder == 0 {
+        return Ok(quotient);
+    }
+    let round_up = match rounding {
+        RoundingMode::Floor => false,
+        RoundingMode::Ceil => true,
+        RoundingMode::HalfEven => {
+            let twice = (remainder >> 127, remainder << 1);
+            twice > divisor || (twice == divisor && quotient % 2 == 1)
+        }
+        RoundingMode::Strict => {
+            return Err(ErrorCode::PrecisionLoss.with_operands(quotient, remainder));
+        }
+    };
+    if round_up {
+        quotient
+            .checked_add(1)
+            .ok_or_else(|| ErrorCode::Overflow.with_operands(quotient, 1))
+    } else {
+        Ok(quotient)
+    }
+}
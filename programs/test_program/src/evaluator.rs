@@ -0,0 +1,133 @@
+//! Stack machine for evaluating RPN bytecode programs
+//!
+//! A program is a sequence of one-byte opcodes. `PUSH` is followed by an
+//! 8-byte little-endian u64 operand; every other opcode pops its inputs from
+//! the stack and pushes a single result.
+
+use anchor_lang::prelude::*;
+
+use crate::ErrorCode;
+
+/// Maximum program size in bytes
+pub const MAX_PROGRAM_LEN: usize = 512;
+/// Maximum number of values held on the stack at once
+pub const MAX_STACK_DEPTH: usize = 32;
+
+pub mod opcode {
+    pub const PUSH: u8 = 0x00;
+    pub const ADD: u8 = 0x01;
+    pub const SUB: u8 = 0x02;
+    pub const MUL: u8 = 0x03;
+    pub const MAX: u8 = 0x04;
+    pub const DIV: u8 = 0x05;
+    pub const MOD: u8 = 0x06;
+    pub const DUP: u8 = 0x07;
+    pub const SWAP: u8 = 0x08;
+}
+
+struct Stack {
+    values: [u64; MAX_STACK_DEPTH],
+    len: usize,
+}
+
+impl Stack {
+    fn push(&mut self, value: u64) -> Result<()> {
+        if self.len == MAX_STACK_DEPTH {
+            return Err(ErrorCode::StackOverflow.with_operands(value, MAX_STACK_DEPTH));
+        }
+        self.values[self.len] = value;
+        self.len += 1;
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Result<u64> {
+        if self.len == 0 {
+            return err!(ErrorCode::StackUnderflow);
+        }
+        self.len -= 1;
+        Ok(self.values[self.len])
+    }
+
+    /// Pop the two topmost values, returning them in push order
+    fn pop_pair(&mut self) -> Result<(u64, u64)> {
+        let b = self.pop()?;
+        let a = self.pop()?;
+        Ok((a, b))
+    }
+}
+
+/// Run `program` and return the value left on top of the stack
+pub fn evaluate(program: &[u8]) -> Result<u64> {
+    if program.len() > MAX_PROGRAM_LEN {
+        return Err(ErrorCode::ProgramTooLong.with_operands(program.len(), MAX_PROGRAM_LEN));
+    }
+    let mut stack = Stack {
+        values: [0; MAX_STACK_DEPTH],
+        len: 0,
+    };
+    let mut pc = 0;
+    while pc < program.len() {
+        let op = program[pc];
+        pc += 1;
+        match op {
+            opcode::PUSH => {
+                let bytes = program
+                    .get(pc..pc + 8)
+                    .ok_or_else(|| ErrorCode::InvalidOperand.with_operands(op, pc))?;
+                stack.push(u64::from_le_bytes(bytes.try_into().unwrap()))?;
+                pc += 8;
+            }
+            opcode::ADD => {
+                let (a, b) = stack.pop_pair()?;
+                stack.push(
+                    a.checked_add(b)
+                        .ok_or_else(|| ErrorCode::Overflow.with_operands(a, b))?,
+                )?;
+            }
+            opcode::SUB => {
+                let (a, b) = stack.pop_pair()?;
+                stack.push(
+                    a.checked_sub(b)
+                        .ok_or_else(|| ErrorCode::Underflow.with_operands(a, b))?,
+                )?;
+            }
+            opcode::MUL => {
+                let (a, b) = stack.pop_pair()?;
+                stack.push(
+                    a.checked_mul(b)
+                        .ok_or_else(|| ErrorCode::Overflow.with_operands(a, b))?,
+                )?;
+            }
+            opcode::MAX => {
+                let (a, b) = stack.pop_pair()?;
+                stack.push(a.max(b))?;
+            }
+            opcode::DIV => {
+                let (a, b) = stack.pop_pair()?;
+                stack.push(
+                    a.checked_div(b)
+                        .ok_or_else(|| ErrorCode::DivideByZero.with_operands(a, b))?,
+                )?;
+            }
+            opcode::MOD => {
+                let (a, b) = stack.pop_pair()?;
+                stack.push(
+                    a.checked_rem(b)
+                        .ok_or_else(|| ErrorCode::DivideByZero.with_operands(a, b))?,
+                )?;
+            }
+            opcode::DUP => {
+                let a = stack.pop()?;
+                stack.push(a)?;
+                stack.push(a)?;
+            }
+            opcode::SWAP => {
+                let (a, b) = stack.pop_pair()?;
+                stack.push(b)?;
+                stack.push(a)?;
+            }
+            _ => return Err(ErrorCode::InvalidOperand.with_operands(op, pc - 1)),
+        }
+    }
+    stack.pop()
+}
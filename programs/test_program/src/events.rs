@@ -0,0 +1,67 @@
+//! Structured events emitted alongside the human-readable logs
+
+use anchor_lang::prelude::*;
+
+use crate::Add;
+
+/// Kind of arithmetic performed by an instruction, used in events and pause bits
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OperationKind {
+    Add,
+    Subtract,
+    Multiply,
+    Max,
+    Divide,
+    Modulo,
+    Batch,
+    Evaluate,
+    Power,
+    Root,
+    Logarithm,
+    Min,
+    Clamp,
+    AbsDiff,
+    Midpoint,
+    Stats,
+}
+
+impl OperationKind {
+    /// Bit for this operation in `Config::paused_operations`
+    pub fn mask(self) -> u32 {
+        1 << self as u32
+    }
+}
+
+#[event]
+pub struct ArithmeticEvent {
+    pub operation: OperationKind,
+    pub a: u64,
+    pub b: u64,
+    pub result: u64,
+    /// Signer passed as the optional `caller` account, if any
+    pub caller: Option<Pubkey>,
+    pub slot: u64,
+}
+
+/// Emit an `ArithmeticEvent`, through a self-CPI when built with `event-cpi`
+pub fn emit_arithmetic(
+    ctx: &Context<Add>,
+    operation: OperationKind,
+    a: u64,
+    b: u64,
+    result: u64,
+) -> Result<()> {
+    let event = ArithmeticEvent {
+        operation,
+        a,
+        b,
+        result,
+        caller: ctx.accounts.caller.as_ref().map(|caller| caller.key()),
+        slot: Clock::get()?.slot,
+    };
+    #[cfg(feature = "event-cpi")]
+    emit_cpi!(event);
+    #[cfg(not(feature = "event-cpi"))]
+    emit!(event);
+    Ok(())
+}
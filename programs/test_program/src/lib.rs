@@ -1,5 +1,32 @@
 use anchor_lang::prelude::*;
 
+/// `msg!` for human-readable progress logs, compiled out by the
+/// `no-verbose-log` feature. Events are emitted either way.
+macro_rules! verbose_msg {
+    ($($arg:tt)*) => {
+        if cfg!(not(feature = "no-verbose-log")) {
+            msg!($($arg)*);
+        }
+    };
+}
+
+pub mod batch;
+/// Not available with `event-cpi`, whose extra accounts the helpers don't pass
+#[cfg(all(feature = "cpi", not(feature = "event-cpi")))]
+pub mod compose;
+pub mod decimal;
+pub mod evaluator;
+pub mod events;
+pub mod math;
+pub mod mode;
+pub mod stats;
+
+pub use batch::{Operand, Operation};
+pub use decimal::{Decimal, RoundingMode};
+pub use events::{ArithmeticEvent, OperationKind};
+pub use mode::ArithmeticMode;
+pub use stats::Stats;
+
 declare_id!("GZzqLG5WuHm9fipCh5PsEyo841F7Kbz9YvNRYynQQY2Z");
 
 #[program]
@@ -8,39 +35,1031 @@ pub mod test_program {
 
     /// Simple addition function
     /// Updated: Added overflow protection
+    #[access_control(ctx.accounts.guard(OperationKind::Add))]
     pub fn add(ctx: Context<Add>, a: u64, b: u64) -> Result<u64> {
-        let result = a.checked_add(b).ok_or(ErrorCode::Overflow)?;
-        msg!("Adding {} + {} = {}", a, b, result);
+        let result = a
+            .checked_add(b)
+            .ok_or_else(|| ErrorCode::Overflow.with_operands(a, b))?;
+        verbose_msg!("Adding {} + {} = {}", a, b, result);
+        events::emit_arithmetic(&ctx, OperationKind::Add, a, b, result)?;
+        ctx.accounts
+            .record_history(OperationKind::Add, a, b, result)?;
         Ok(result)
     }
-    
+
     /// Simple subtraction function
+    #[access_control(ctx.accounts.guard(OperationKind::Subtract))]
     pub fn subtract(ctx: Context<Add>, a: u64, b: u64) -> Result<u64> {
-        let result = a.checked_sub(b).ok_or(ErrorCode::Overflow)?;
-        msg!("Subtracting {} - {} = {}", a, b, result);
+        let result = a
+            .checked_sub(b)
+            .ok_or_else(|| ErrorCode::Underflow.with_operands(a, b))?;
+        verbose_msg!("Subtracting {} - {} = {}", a, b, result);
+        events::emit_arithmetic(&ctx, OperationKind::Subtract, a, b, result)?;
+        ctx.accounts
+            .record_history(OperationKind::Subtract, a, b, result)?;
         Ok(result)
     }
-    
+
     /// Simple multiplication function
+    #[access_control(ctx.accounts.guard(OperationKind::Multiply))]
     pub fn multiply(ctx: Context<Add>, a: u64, b: u64) -> Result<u64> {
-        let result = a.checked_mul(b).ok_or(ErrorCode::Overflow)?;
-        msg!("Multiplying {} * {} = {}", a, b, result);
+        let result = a
+            .checked_mul(b)
+            .ok_or_else(|| ErrorCode::Overflow.with_operands(a, b))?;
+        verbose_msg!("Multiplying {} * {} = {}", a, b, result);
+        events::emit_arithmetic(&ctx, OperationKind::Multiply, a, b, result)?;
+        ctx.accounts
+            .record_history(OperationKind::Multiply, a, b, result)?;
+        Ok(result)
+    }
+
+    /// Addition with caller-selected overflow behaviour
+    #[access_control(ctx.accounts.guard(OperationKind::Add))]
+    pub fn add_with_mode(ctx: Context<Add>, a: u64, b: u64, mode: ArithmeticMode) -> Result<u64> {
+        let result = mode.add(a, b)?;
+        verbose_msg!("Adding {} + {} = {} ({:?})", a, b, result, mode);
+        events::emit_arithmetic(&ctx, OperationKind::Add, a, b, result)?;
+        ctx.accounts
+            .record_history(OperationKind::Add, a, b, result)?;
         Ok(result)
     }
-    
+
+    /// Subtraction with caller-selected overflow behaviour
+    #[access_control(ctx.accounts.guard(OperationKind::Subtract))]
+    pub fn subtract_with_mode(
+        ctx: Context<Add>,
+        a: u64,
+        b: u64,
+        mode: ArithmeticMode,
+    ) -> Result<u64> {
+        let result = mode.sub(a, b)?;
+        verbose_msg!("Subtracting {} - {} = {} ({:?})", a, b, result, mode);
+        events::emit_arithmetic(&ctx, OperationKind::Subtract, a, b, result)?;
+        ctx.accounts
+            .record_history(OperationKind::Subtract, a, b, result)?;
+        Ok(result)
+    }
+
+    /// Multiplication with caller-selected overflow behaviour
+    #[access_control(ctx.accounts.guard(OperationKind::Multiply))]
+    pub fn multiply_with_mode(
+        ctx: Context<Add>,
+        a: u64,
+        b: u64,
+        mode: ArithmeticMode,
+    ) -> Result<u64> {
+        let result = mode.mul(a, b)?;
+        verbose_msg!("Multiplying {} * {} = {} ({:?})", a, b, result, mode);
+        events::emit_arithmetic(&ctx, OperationKind::Multiply, a, b, result)?;
+        ctx.accounts
+            .record_history(OperationKind::Multiply, a, b, result)?;
+        Ok(result)
+    }
+
     /// Get maximum of two numbers
+    #[access_control(ctx.accounts.guard(OperationKind::Max))]
     pub fn max(ctx: Context<Add>, a: u64, b: u64) -> Result<u64> {
         let result = a.max(b);
-        msg!("Maximum of {} and {} is {}", a, b, result);
+        verbose_msg!("Maximum of {} and {} is {}", a, b, result);
+        events::emit_arithmetic(&ctx, OperationKind::Max, a, b, result)?;
+        ctx.accounts
+            .record_history(OperationKind::Max, a, b, result)?;
+        Ok(result)
+    }
+
+    /// Get minimum of two numbers
+    #[access_control(ctx.accounts.guard(OperationKind::Min))]
+    pub fn min(ctx: Context<Add>, a: u64, b: u64) -> Result<u64> {
+        let result = a.min(b);
+        verbose_msg!("Minimum of {} and {} is {}", a, b, result);
+        events::emit_arithmetic(&ctx, OperationKind::Min, a, b, result)?;
+        ctx.accounts
+            .record_history(OperationKind::Min, a, b, result)?;
+        Ok(result)
+    }
+
+    /// Limit `value` to the range `lo..=hi`. Events and history record
+    /// `value` together with `lo` if it was below the range, `hi` otherwise.
+    #[access_control(ctx.accounts.guard(OperationKind::Clamp))]
+    pub fn clamp(ctx: Context<Add>, value: u64, lo: u64, hi: u64) -> Result<u64> {
+        if lo > hi {
+            return Err(ErrorCode::InvalidRange.with_operands(lo, hi));
+        }
+        let (result, bound) = if value < lo {
+            (lo, lo)
+        } else {
+            (value.min(hi), hi)
+        };
+        verbose_msg!("Clamping {} to [{}, {}] is {}", value, lo, hi, result);
+        events::emit_arithmetic(&ctx, OperationKind::Clamp, value, bound, result)?;
+        ctx.accounts
+            .record_history(OperationKind::Clamp, value, bound, result)?;
+        Ok(result)
+    }
+
+    /// Absolute difference of two numbers
+    #[access_control(ctx.accounts.guard(OperationKind::AbsDiff))]
+    pub fn abs_diff(ctx: Context<Add>, a: u64, b: u64) -> Result<u64> {
+        let result = a.abs_diff(b);
+        verbose_msg!("Absolute difference of {} and {} is {}", a, b, result);
+        events::emit_arithmetic(&ctx, OperationKind::AbsDiff, a, b, result)?;
+        ctx.accounts
+            .record_history(OperationKind::AbsDiff, a, b, result)?;
+        Ok(result)
+    }
+
+    /// Average of two numbers, rounded down, without overflowing
+    #[access_control(ctx.accounts.guard(OperationKind::Midpoint))]
+    pub fn midpoint(ctx: Context<Add>, a: u64, b: u64) -> Result<u64> {
+        let result = (a & b) + ((a ^ b) >> 1);
+        verbose_msg!("Midpoint of {} and {} is {}", a, b, result);
+        events::emit_arithmetic(&ctx, OperationKind::Midpoint, a, b, result)?;
+        ctx.accounts
+            .record_history(OperationKind::Midpoint, a, b, result)?;
+        Ok(result)
+    }
+
+    /// Integer division, rounding toward zero
+    #[access_control(ctx.accounts.guard(OperationKind::Divide))]
+    pub fn divide(ctx: Context<Add>, a: u64, b: u64) -> Result<u64> {
+        let result = a
+            .checked_div(b)
+            .ok_or_else(|| ErrorCode::DivideByZero.with_operands(a, b))?;
+        verbose_msg!("Dividing {} / {} = {}", a, b, result);
+        Ok(result)
+    }
+
+    /// Remainder of integer division
+    #[access_control(ctx.accounts.guard(OperationKind::Modulo))]
+    pub fn modulo(ctx: Context<Add>, a: u64, b: u64) -> Result<u64> {
+        let result = a
+            .checked_rem(b)
+            .ok_or_else(|| ErrorCode::DivideByZero.with_operands(a, b))?;
+        verbose_msg!("Modulo {} % {} = {}", a, b, result);
+        Ok(result)
+    }
+
+    /// Integer division, rounding up
+    #[access_control(ctx.accounts.guard(OperationKind::Divide))]
+    pub fn div_ceil(ctx: Context<Add>, a: u64, b: u64) -> Result<u64> {
+        if b == 0 {
+            return Err(ErrorCode::DivideByZero.with_operands(a, b));
+        }
+        let result = a.div_ceil(b);
+        verbose_msg!("Dividing {} / {} rounded up = {}", a, b, result);
+        Ok(result)
+    }
+
+    /// Integer division returning both quotient and remainder
+    #[access_control(ctx.accounts.guard(OperationKind::Divide))]
+    pub fn div_rem(ctx: Context<Add>, a: u64, b: u64) -> Result<DivRem> {
+        let quotient = a
+            .checked_div(b)
+            .ok_or_else(|| ErrorCode::DivideByZero.with_operands(a, b))?;
+        let remainder = a
+            .checked_rem(b)
+            .ok_or_else(|| ErrorCode::DivideByZero.with_operands(a, b))?;
+        verbose_msg!(
+            "Dividing {} / {} = {} remainder {}",
+            a,
+            b,
+            quotient,
+            remainder
+        );
+        Ok(DivRem {
+            quotient,
+            remainder,
+        })
+    }
+
+    /// Square root, rounded down
+    #[access_control(ctx.accounts.guard(OperationKind::Root))]
+    pub fn isqrt(ctx: Context<Add>, value: u64) -> Result<u64> {
+        let result = math::isqrt(value);
+        verbose_msg!("Square root of {} = {}", value, result);
+        Ok(result)
+    }
+
+    /// `base` raised to `exp`
+    #[access_control(ctx.accounts.guard(OperationKind::Power))]
+    pub fn checked_pow(ctx: Context<Add>, base: u64, exp: u32) -> Result<u64> {
+        let result = base
+            .checked_pow(exp)
+            .ok_or_else(|| ErrorCode::Overflow.with_operands(base, exp))?;
+        verbose_msg!("Raising {} ^ {} = {}", base, exp, result);
+        Ok(result)
+    }
+
+    /// Base 2 logarithm, rounded down
+    #[access_control(ctx.accounts.guard(OperationKind::Logarithm))]
+    pub fn ilog2(ctx: Context<Add>, value: u64) -> Result<u32> {
+        let result = value
+            .checked_ilog2()
+            .ok_or_else(|| ErrorCode::InvalidOperand.with_operands(value, 2))?;
+        verbose_msg!("Log2 of {} = {}", value, result);
+        Ok(result)
+    }
+
+    /// Base 10 logarithm, rounded down
+    #[access_control(ctx.accounts.guard(OperationKind::Logarithm))]
+    pub fn ilog10(ctx: Context<Add>, value: u64) -> Result<u32> {
+        let result = value
+            .checked_ilog10()
+            .ok_or_else(|| ErrorCode::InvalidOperand.with_operands(value, 10))?;
+        verbose_msg!("Log10 of {} = {}", value, result);
+        Ok(result)
+    }
+
+    /// `n`th root, rounded down
+    #[access_control(ctx.accounts.guard(OperationKind::Root))]
+    pub fn nth_root(ctx: Context<Add>, value: u64, n: u32) -> Result<u64> {
+        let result = math::nth_root(value, n)
+            .ok_or_else(|| ErrorCode::InvalidOperand.with_operands(value, n))?;
+        verbose_msg!("Root {} of {} = {}", n, value, result);
         Ok(result)
     }
+
+    /// 128-bit addition
+    #[access_control(ctx.accounts.guard(OperationKind::Add))]
+    pub fn add_u128(ctx: Context<Add>, a: u128, b: u128) -> Result<u128> {
+        let result = a
+            .checked_add(b)
+            .ok_or_else(|| ErrorCode::Overflow.with_operands(a, b))?;
+        verbose_msg!("Adding {} + {} = {}", a, b, result);
+        Ok(result)
+    }
+
+    /// 128-bit subtraction
+    #[access_control(ctx.accounts.guard(OperationKind::Subtract))]
+    pub fn sub_u128(ctx: Context<Add>, a: u128, b: u128) -> Result<u128> {
+        let result = a
+            .checked_sub(b)
+            .ok_or_else(|| ErrorCode::Underflow.with_operands(a, b))?;
+        verbose_msg!("Subtracting {} - {} = {}", a, b, result);
+        Ok(result)
+    }
+
+    /// 128-bit multiplication
+    #[access_control(ctx.accounts.guard(OperationKind::Multiply))]
+    pub fn mul_u128(ctx: Context<Add>, a: u128, b: u128) -> Result<u128> {
+        let result = a
+            .checked_mul(b)
+            .ok_or_else(|| ErrorCode::Overflow.with_operands(a, b))?;
+        verbose_msg!("Multiplying {} * {} = {}", a, b, result);
+        Ok(result)
+    }
+
+    /// 128-bit division, rounding toward zero
+    #[access_control(ctx.accounts.guard(OperationKind::Divide))]
+    pub fn div_u128(ctx: Context<Add>, a: u128, b: u128) -> Result<u128> {
+        let result = a
+            .checked_div(b)
+            .ok_or_else(|| ErrorCode::DivideByZero.with_operands(a, b))?;
+        verbose_msg!("Dividing {} / {} = {}", a, b, result);
+        Ok(result)
+    }
+
+    /// Compute `a * b / c` with a 256-bit intermediate, rounding down
+    #[access_control(ctx.accounts.guard(OperationKind::Multiply))]
+    pub fn mul_div_u128(ctx: Context<Add>, a: u128, b: u128, c: u128) -> Result<u128> {
+        if c == 0 {
+            // `a * b` may not fit in a u128, so report it unevaluated
+            return Err(ErrorCode::DivideByZero.with_operands(format!("{a} * {b}"), c));
+        }
+        let result =
+            math::mul_div_u128(a, b, c).ok_or_else(|| ErrorCode::Overflow.with_operands(a, b))?;
+        verbose_msg!("Computing {} * {} / {} = {}", a, b, c, result);
+        Ok(result)
+    }
+
+    /// Compute `a * b / c` with a 128-bit intermediate, rounding down
+    #[access_control(ctx.accounts.guard(OperationKind::Multiply))]
+    pub fn mul_div_floor(ctx: Context<Add>, a: u64, b: u64, c: u64) -> Result<u64> {
+        let result = math::mul_div_u64(a, b, c, RoundingMode::Floor)?;
+        verbose_msg!("Computing {} * {} / {} rounded down = {}", a, b, c, result);
+        Ok(result)
+    }
+
+    /// Compute `a * b / c` with a 128-bit intermediate, rounding up
+    #[access_control(ctx.accounts.guard(OperationKind::Multiply))]
+    pub fn mul_div_ceil(ctx: Context<Add>, a: u64, b: u64, c: u64) -> Result<u64> {
+        let result = math::mul_div_u64(a, b, c, RoundingMode::Ceil)?;
+        verbose_msg!("Computing {} * {} / {} rounded up = {}", a, b, c, result);
+        Ok(result)
+    }
+
+    /// Compute `a * b / c` with a 128-bit intermediate, rounding to nearest
+    /// with ties to even
+    #[access_control(ctx.accounts.guard(OperationKind::Multiply))]
+    pub fn mul_div_round(ctx: Context<Add>, a: u64, b: u64, c: u64) -> Result<u64> {
+        let result = math::mul_div_u64(a, b, c, RoundingMode::HalfEven)?;
+        verbose_msg!("Computing {} * {} / {} rounded = {}", a, b, c, result);
+        Ok(result)
+    }
+
+    /// Signed 64-bit addition
+    #[access_control(ctx.accounts.guard(OperationKind::Add))]
+    pub fn add_i64(ctx: Context<Add>, a: i64, b: i64) -> Result<i64> {
+        let result = a
+            .checked_add(b)
+            .ok_or_else(|| ErrorCode::out_of_range(b < 0).with_operands(a, b))?;
+        verbose_msg!("Adding {} + {} = {}", a, b, result);
+        Ok(result)
+    }
+
+    /// Signed 64-bit subtraction
+    #[access_control(ctx.accounts.guard(OperationKind::Subtract))]
+    pub fn sub_i64(ctx: Context<Add>, a: i64, b: i64) -> Result<i64> {
+        let result = a
+            .checked_sub(b)
+            .ok_or_else(|| ErrorCode::out_of_range(b > 0).with_operands(a, b))?;
+        verbose_msg!("Subtracting {} - {} = {}", a, b, result);
+        Ok(result)
+    }
+
+    /// Signed 64-bit multiplication
+    #[access_control(ctx.accounts.guard(OperationKind::Multiply))]
+    pub fn mul_i64(ctx: Context<Add>, a: i64, b: i64) -> Result<i64> {
+        let result = a
+            .checked_mul(b)
+            .ok_or_else(|| ErrorCode::out_of_range((a < 0) != (b < 0)).with_operands(a, b))?;
+        verbose_msg!("Multiplying {} * {} = {}", a, b, result);
+        Ok(result)
+    }
+
+    /// Signed 128-bit addition
+    #[access_control(ctx.accounts.guard(OperationKind::Add))]
+    pub fn add_i128(ctx: Context<Add>, a: i128, b: i128) -> Result<i128> {
+        let result = a
+            .checked_add(b)
+            .ok_or_else(|| ErrorCode::out_of_range(b < 0).with_operands(a, b))?;
+        verbose_msg!("Adding {} + {} = {}", a, b, result);
+        Ok(result)
+    }
+
+    /// Signed 128-bit subtraction
+    #[access_control(ctx.accounts.guard(OperationKind::Subtract))]
+    pub fn sub_i128(ctx: Context<Add>, a: i128, b: i128) -> Result<i128> {
+        let result = a
+            .checked_sub(b)
+            .ok_or_else(|| ErrorCode::out_of_range(b > 0).with_operands(a, b))?;
+        verbose_msg!("Subtracting {} - {} = {}", a, b, result);
+        Ok(result)
+    }
+
+    /// Signed 128-bit multiplication
+    #[access_control(ctx.accounts.guard(OperationKind::Multiply))]
+    pub fn mul_i128(ctx: Context<Add>, a: i128, b: i128) -> Result<i128> {
+        let result = a
+            .checked_mul(b)
+            .ok_or_else(|| ErrorCode::out_of_range((a < 0) != (b < 0)).with_operands(a, b))?;
+        verbose_msg!("Multiplying {} * {} = {}", a, b, result);
+        Ok(result)
+    }
+
+    /// Fixed-point decimal addition
+    #[access_control(ctx.accounts.guard(OperationKind::Add))]
+    pub fn decimal_add(
+        ctx: Context<Add>,
+        a: Decimal,
+        b: Decimal,
+        scale: u8,
+        rounding: RoundingMode,
+    ) -> Result<Decimal> {
+        let result = a.checked_add(b, scale, rounding)?;
+        verbose_msg!("Adding {} + {} = {}", a, b, result);
+        Ok(result)
+    }
+
+    /// Fixed-point decimal subtraction
+    #[access_control(ctx.accounts.guard(OperationKind::Subtract))]
+    pub fn decimal_sub(
+        ctx: Context<Add>,
+        a: Decimal,
+        b: Decimal,
+        scale: u8,
+        rounding: RoundingMode,
+    ) -> Result<Decimal> {
+        let result = a.checked_sub(b, scale, rounding)?;
+        verbose_msg!("Subtracting {} - {} = {}", a, b, result);
+        Ok(result)
+    }
+
+    /// Fixed-point decimal multiplication
+    #[access_control(ctx.accounts.guard(OperationKind::Multiply))]
+    pub fn decimal_mul(
+        ctx: Context<Add>,
+        a: Decimal,
+        b: Decimal,
+        scale: u8,
+        rounding: RoundingMode,
+    ) -> Result<Decimal> {
+        let result = a.checked_mul(b, scale, rounding)?;
+        verbose_msg!("Multiplying {} * {} = {}", a, b, result);
+        Ok(result)
+    }
+
+    /// Fixed-point decimal division
+    #[access_control(ctx.accounts.guard(OperationKind::Divide))]
+    pub fn decimal_div(
+        ctx: Context<Add>,
+        a: Decimal,
+        b: Decimal,
+        scale: u8,
+        rounding: RoundingMode,
+    ) -> Result<Decimal> {
+        let result = a.checked_div(b, scale, rounding)?;
+        verbose_msg!("Dividing {} / {} = {}", a, b, result);
+        Ok(result)
+    }
+
+    /// Evaluate a list of operations, returning every intermediate result
+    #[access_control(ctx.accounts.guard(OperationKind::Batch))]
+    pub fn execute_batch(ctx: Context<Add>, operations: Vec<Operation>) -> Result<Vec<u64>> {
+        let results = batch::execute(&operations)?;
+        verbose_msg!("Executed batch of {} operations", results.len());
+        Ok(results)
+    }
+
+    /// Sum, mean, median, min, max and population variance of `values`
+    #[access_control(ctx.accounts.guard(OperationKind::Stats))]
+    pub fn stats(ctx: Context<Add>, values: Vec<u64>) -> Result<Stats> {
+        let stats = stats::compute(values)?;
+        verbose_msg!(
+            "Sum {} mean {} median {} min {} max {} variance {}",
+            stats.sum,
+            stats.mean,
+            stats.median,
+            stats.min,
+            stats.max,
+            stats.variance
+        );
+        Ok(stats)
+    }
+
+    /// Run an RPN bytecode program and return the value on top of the stack
+    #[access_control(ctx.accounts.guard(OperationKind::Evaluate))]
+    pub fn evaluate(ctx: Context<Add>, program: Vec<u8>) -> Result<u64> {
+        let result = evaluator::evaluate(&program)?;
+        verbose_msg!("Evaluated {} byte program = {}", program.len(), result);
+        Ok(result)
+    }
+
+    /// Create the program config; only the upgrade authority may do this
+    pub fn initialize_config(ctx: Context<InitializeConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.pending_admin = None;
+        config.paused = false;
+        config.paused_operations = 0;
+        config.restrict_callers = false;
+        config.allowed_callers = Vec::new();
+        config.bump = ctx.bumps.config;
+        verbose_msg!("Initialized config with admin {}", config.admin);
+        Ok(())
+    }
+
+    /// Nominate a new admin; the transfer completes once they call `accept_admin`
+    pub fn propose_admin(ctx: Context<UpdateConfig>, new_admin: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.pending_admin = Some(new_admin);
+        verbose_msg!(
+            "Proposed admin transfer from {} to {}",
+            config.admin,
+            new_admin
+        );
+        Ok(())
+    }
+
+    /// Complete a pending admin transfer as the nominated key
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        verbose_msg!(
+            "Changing admin from {} to {}",
+            config.admin,
+            ctx.accounts.pending_admin.key()
+        );
+        config.admin = ctx.accounts.pending_admin.key();
+        config.pending_admin = None;
+        Ok(())
+    }
+
+    /// Withdraw a pending admin transfer
+    pub fn cancel_admin_transfer(ctx: Context<UpdateConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let pending_admin = config
+            .pending_admin
+            .take()
+            .ok_or(ErrorCode::NoPendingAdmin)?;
+        verbose_msg!("Cancelled admin transfer to {}", pending_admin);
+        Ok(())
+    }
+
+    /// Allow `caller` to invoke arithmetic instructions while callers are restricted
+    pub fn add_caller(ctx: Context<UpdateConfig>, caller: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(
+            !config.allowed_callers.contains(&caller),
+            ErrorCode::CallerAlreadyAllowed
+        );
+        require!(
+            config.allowed_callers.len() < Config::MAX_CALLERS,
+            ErrorCode::AllowlistFull
+        );
+        config.allowed_callers.push(caller);
+        verbose_msg!("Added caller {}", caller);
+        Ok(())
+    }
+
+    /// Remove `caller` from the allowlist
+    pub fn remove_caller(ctx: Context<UpdateConfig>, caller: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let index = config
+            .allowed_callers
+            .iter()
+            .position(|allowed| *allowed == caller)
+            .ok_or(ErrorCode::CallerNotAllowed)?;
+        config.allowed_callers.swap_remove(index);
+        verbose_msg!("Removed caller {}", caller);
+        Ok(())
+    }
+
+    /// Turn the caller allowlist check on or off
+    pub fn set_caller_restriction(ctx: Context<UpdateConfig>, enabled: bool) -> Result<()> {
+        ctx.accounts.config.restrict_callers = enabled;
+        verbose_msg!("Caller restriction enabled: {}", enabled);
+        Ok(())
+    }
+
+    /// Halt every arithmetic instruction
+    pub fn pause(ctx: Context<UpdateConfig>) -> Result<()> {
+        ctx.accounts.config.paused = true;
+        verbose_msg!("Program paused");
+        Ok(())
+    }
+
+    /// Resume arithmetic instructions, except any paused individually
+    pub fn unpause(ctx: Context<UpdateConfig>) -> Result<()> {
+        ctx.accounts.config.paused = false;
+        verbose_msg!("Program unpaused");
+        Ok(())
+    }
+
+    /// Pause or resume a single kind of operation
+    pub fn set_operation_paused(
+        ctx: Context<UpdateConfig>,
+        operation: OperationKind,
+        paused: bool,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        if paused {
+            config.paused_operations |= operation.mask();
+        } else {
+            config.paused_operations &= !operation.mask();
+        }
+        verbose_msg!("Operation {:?} paused: {}", operation, paused);
+        Ok(())
+    }
+
+    /// Create the caller's operation history ring buffer
+    pub fn initialize_history(ctx: Context<InitializeHistory>) -> Result<()> {
+        let mut history = ctx.accounts.history.load_init()?;
+        history.authority = ctx.accounts.authority.key();
+        verbose_msg!("Initialized history with capacity {}", History::CAPACITY);
+        Ok(())
+    }
+
+    /// Drop every entry from the caller's history
+    pub fn clear_history(ctx: Context<ClearHistory>) -> Result<()> {
+        let mut history = ctx.accounts.history.load_mut()?;
+        history.clear();
+        verbose_msg!("Cleared history");
+        Ok(())
+    }
+
+    /// Create the caller's accumulator account with a starting value
+    pub fn initialize_accumulator(ctx: Context<InitializeAccumulator>, initial: u64) -> Result<()> {
+        let accumulator = &mut ctx.accounts.accumulator;
+        accumulator.authority = ctx.accounts.authority.key();
+        accumulator.value = initial;
+        accumulator.op_count = 0;
+        accumulator.last_updated_slot = Clock::get()?.slot;
+        accumulator.bump = ctx.bumps.accumulator;
+        verbose_msg!("Initialized accumulator with {}", initial);
+        Ok(())
+    }
+
+    /// Add to the accumulator value
+    #[access_control(ctx.accounts.guard(OperationKind::Add))]
+    pub fn accumulate_add(ctx: Context<Accumulate>, amount: u64) -> Result<u64> {
+        let accumulator = &mut ctx.accounts.accumulator;
+        let result = accumulator
+            .value
+            .checked_add(amount)
+            .ok_or_else(|| ErrorCode::Overflow.with_operands(accumulator.value, amount))?;
+        verbose_msg!(
+            "Accumulating {} + {} = {}",
+            accumulator.value,
+            amount,
+            result
+        );
+        accumulator.record(result)
+    }
+
+    /// Subtract from the accumulator value
+    #[access_control(ctx.accounts.guard(OperationKind::Subtract))]
+    pub fn accumulate_sub(ctx: Context<Accumulate>, amount: u64) -> Result<u64> {
+        let accumulator = &mut ctx.accounts.accumulator;
+        let result = accumulator
+            .value
+            .checked_sub(amount)
+            .ok_or_else(|| ErrorCode::Underflow.with_operands(accumulator.value, amount))?;
+        verbose_msg!(
+            "Accumulating {} - {} = {}",
+            accumulator.value,
+            amount,
+            result
+        );
+        accumulator.record(result)
+    }
+
+    /// Multiply the accumulator value
+    #[access_control(ctx.accounts.guard(OperationKind::Multiply))]
+    pub fn accumulate_mul(ctx: Context<Accumulate>, amount: u64) -> Result<u64> {
+        let accumulator = &mut ctx.accounts.accumulator;
+        let result = accumulator
+            .value
+            .checked_mul(amount)
+            .ok_or_else(|| ErrorCode::Overflow.with_operands(accumulator.value, amount))?;
+        verbose_msg!(
+            "Accumulating {} * {} = {}",
+            accumulator.value,
+            amount,
+            result
+        );
+        accumulator.record(result)
+    }
+
+    /// Replace the accumulator value with the maximum of it and `amount`
+    #[access_control(ctx.accounts.guard(OperationKind::Max))]
+    pub fn accumulate_max(ctx: Context<Accumulate>, amount: u64) -> Result<u64> {
+        let accumulator = &mut ctx.accounts.accumulator;
+        let result = accumulator.value.max(amount);
+        verbose_msg!(
+            "Accumulating max of {} and {} is {}",
+            accumulator.value,
+            amount,
+            result
+        );
+        accumulator.record(result)
+    }
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct Add<'info> {
+    /// CHECK: may be uninitialized, in which case no restrictions apply; see `Config::load`
+    #[account(seeds = [Config::SEED], bump)]
+    pub config: UncheckedAccount<'info>,
+    /// Optional signer recorded as the caller in emitted events
+    pub caller: Option<Signer<'info>>,
+    /// Optional history owned by `caller` that results are appended to
+    #[account(mut)]
+    pub history: Option<AccountLoader<'info, History>>,
+}
+
+impl<'info> Add<'info> {
+    /// Reject the call if `operation` is paused, or if the config restricts
+    /// callers and the signer is not allowed
+    pub fn guard(&self, operation: OperationKind) -> Result<()> {
+        let Some(config) = Config::load(&self.config)? else {
+            return Ok(());
+        };
+        config.check_not_paused(operation)?;
+        config.check_caller(self.caller.as_ref().map(|caller| caller.key))
+    }
+
+    /// Append a result to the history account, if one was passed
+    pub fn record_history(
+        &self,
+        operation: OperationKind,
+        a: u64,
+        b: u64,
+        result: u64,
+    ) -> Result<()> {
+        let Some(history) = &self.history else {
+            return Ok(());
+        };
+        let caller = self.caller.as_ref().ok_or(ErrorCode::Unauthorized)?;
+        let mut history = history.load_mut()?;
+        require_keys_eq!(history.authority, caller.key(), ErrorCode::Unauthorized);
+        history.push(HistoryEntry {
+            a,
+            b,
+            result,
+            slot: Clock::get()?.slot,
+            operation: operation as u8,
+            _padding: [0; 7],
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Config::INIT_SPACE,
+        seeds = [Config::SEED],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, program::TestProgram>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ ErrorCode::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct Add {}
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [Config::SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [Config::SEED],
+        bump = config.bump,
+        constraint = config.pending_admin == Some(pending_admin.key()) @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    pub pending_admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeAccumulator<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Accumulator::INIT_SPACE,
+        seeds = [Accumulator::SEED, authority.key().as_ref()],
+        bump
+    )]
+    pub accumulator: Account<'info, Accumulator>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Accumulate<'info> {
+    #[account(
+        mut,
+        seeds = [Accumulator::SEED, authority.key().as_ref()],
+        bump = accumulator.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub accumulator: Account<'info, Accumulator>,
+    pub authority: Signer<'info>,
+    /// CHECK: may be uninitialized, in which case no restrictions apply; see `Config::load`
+    #[account(seeds = [Config::SEED], bump)]
+    pub config: UncheckedAccount<'info>,
+}
+
+impl<'info> Accumulate<'info> {
+    /// Reject the call if `operation` is paused, or if the config restricts
+    /// callers and the authority is not allowed
+    pub fn guard(&self, operation: OperationKind) -> Result<()> {
+        let Some(config) = Config::load(&self.config)? else {
+            return Ok(());
+        };
+        config.check_not_paused(operation)?;
+        config.check_caller(Some(self.authority.key))
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeHistory<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<History>(),
+        seeds = [History::SEED, authority.key().as_ref()],
+        bump
+    )]
+    pub history: AccountLoader<'info, History>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClearHistory<'info> {
+    #[account(mut, has_one = authority @ ErrorCode::Unauthorized)]
+    pub history: AccountLoader<'info, History>,
+    pub authority: Signer<'info>,
+}
+
+/// Quotient and remainder returned by `div_rem`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DivRem {
+    pub quotient: u64,
+    pub remainder: u64,
+}
+
+/// Capacity of `Config::allowed_callers`; `max_len` only accepts a bare identifier
+const MAX_CALLERS: usize = 16;
+
+/// Program-wide settings controlled by the admin
+#[account]
+#[derive(InitSpace)]
+pub struct Config {
+    pub admin: Pubkey,
+    /// Key nominated by `propose_admin`, waiting to call `accept_admin`
+    pub pending_admin: Option<Pubkey>,
+    /// Halts every arithmetic instruction when set
+    pub paused: bool,
+    /// Bitmask of individually paused operations, see `OperationKind::mask`
+    pub paused_operations: u32,
+    /// When set, arithmetic instructions require a `caller`, and accumulate
+    /// instructions an authority, from `allowed_callers`
+    pub restrict_callers: bool,
+    #[max_len(MAX_CALLERS)]
+    pub allowed_callers: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl Config {
+    pub const SEED: &'static [u8] = b"config";
+    pub const MAX_CALLERS: usize = MAX_CALLERS;
+
+    /// Read the config PDA, or `None` if it has not been created yet
+    pub fn load(info: &AccountInfo) -> Result<Option<Self>> {
+        if info.data_is_empty() {
+            return Ok(None);
+        }
+        if info.owner != &crate::ID {
+            return Err(
+                Error::from(anchor_lang::error::ErrorCode::AccountOwnedByWrongProgram)
+                    .with_pubkeys((*info.owner, crate::ID)),
+            );
+        }
+        Config::try_deserialize(&mut &info.try_borrow_data()?[..]).map(Some)
+    }
+
+    /// Reject `caller` if callers are restricted and it is missing or not allowed
+    pub fn check_caller(&self, caller: Option<&Pubkey>) -> Result<()> {
+        if !self.restrict_callers {
+            return Ok(());
+        }
+        let caller = caller.ok_or(ErrorCode::Unauthorized)?;
+        require!(
+            self.allowed_callers.contains(caller),
+            ErrorCode::Unauthorized
+        );
+        Ok(())
+    }
+
+    pub fn check_not_paused(&self, operation: OperationKind) -> Result<()> {
+        if self.paused || self.paused_operations & operation.mask() != 0 {
+            msg!("Operation {:?} is paused", operation);
+            return err!(ErrorCode::ProgramPaused);
+        }
+        Ok(())
+    }
+}
+
+/// Running total owned by a single authority
+#[account]
+#[derive(InitSpace)]
+pub struct Accumulator {
+    pub authority: Pubkey,
+    pub value: u64,
+    pub op_count: u64,
+    pub last_updated_slot: u64,
+    pub bump: u8,
+}
+
+impl Accumulator {
+    pub const SEED: &'static [u8] = b"accumulator";
+
+    /// Store a new value and bump the bookkeeping fields
+    fn record(&mut self, value: u64) -> Result<u64> {
+        self.value = value;
+        self.op_count = self.op_count.checked_add(1).ok_or(ErrorCode::Overflow)?;
+        self.last_updated_slot = Clock::get()?.slot;
+        Ok(value)
+    }
+}
+
+/// Fixed-capacity ring buffer of recent results for a single authority
+#[account(zero_copy)]
+pub struct History {
+    pub authority: Pubkey,
+    /// Index the next entry will be written to
+    pub head: u32,
+    /// Number of valid entries, at most `CAPACITY`
+    pub len: u32,
+    pub entries: [HistoryEntry; History::CAPACITY],
+}
+
+impl History {
+    pub const SEED: &'static [u8] = b"history";
+    pub const CAPACITY: usize = 128;
+
+    /// Write `entry`, overwriting the oldest one once the buffer is full
+    pub fn push(&mut self, entry: HistoryEntry) {
+        self.entries[self.head as usize] = entry;
+        self.head = (self.head + 1) % Self::CAPACITY as u32;
+        self.len = (self.len + 1).min(Self::CAPACITY as u32);
+    }
+
+    pub fn clear(&mut self) {
+        // Fill in place: a temporary array would overflow the 4 KB SBF stack frame
+        self.entries.fill(HistoryEntry::default());
+        self.head = 0;
+        self.len = 0;
+    }
+}
+
+#[zero_copy]
+#[derive(Default, Debug)]
+pub struct HistoryEntry {
+    pub a: u64,
+    pub b: u64,
+    pub result: u64,
+    pub slot: u64,
+    /// `OperationKind` discriminant
+    pub operation: u8,
+    pub _padding: [u8; 7],
+}
 
 #[error_code]
 pub enum ErrorCode {
     #[msg("Overflow occurred")]
     Overflow,
+    #[msg("Division by zero")]
+    DivideByZero,
+    #[msg("Underflow occurred")]
+    Underflow,
+    #[msg("Invalid operand")]
+    InvalidOperand,
+    #[msg("Unauthorized")]
+    Unauthorized,
+    #[msg("Result cannot be represented at the requested scale without rounding")]
+    PrecisionLoss,
+    #[msg("Too many operations in batch")]
+    BatchTooLarge,
+    #[msg("Not enough values on the stack")]
+    StackUnderflow,
+    #[msg("Stack depth limit exceeded")]
+    StackOverflow,
+    #[msg("Program exceeds the maximum length")]
+    ProgramTooLong,
+    #[msg("Caller allowlist is full")]
+    AllowlistFull,
+    #[msg("Caller is already on the allowlist")]
+    CallerAlreadyAllowed,
+    #[msg("Caller is not on the allowlist")]
+    CallerNotAllowed,
+    #[msg("No admin transfer is pending")]
+    NoPendingAdmin,
+    #[msg("Program is paused")]
+    ProgramPaused,
+    #[msg("Missing or malformed return data")]
+    InvalidReturnData,
+    #[msg("Lower bound is greater than upper bound")]
+    InvalidRange,
+    #[msg("Too many values")]
+    TooManyElements,
+    #[msg("Operation in batch failed")]
+    BatchOperationFailed,
+}
+
+impl ErrorCode {
+    /// Pick `Underflow` or `Overflow` depending on which bound a signed result crossed
+    pub fn out_of_range(negative: bool) -> Self {
+        if negative {
+            ErrorCode::Underflow
+        } else {
+            ErrorCode::Overflow
+        }
+    }
+
+    /// Attach the offending operands so they show up in the transaction logs
+    pub fn with_operands(self, a: impl ToString, b: impl ToString) -> Error {
+        Error::from(self).with_values((a, b))
+    }
 }
@@ -0,0 +1,100 @@
+//! Integer arithmetic helpers beyond what the native types provide
+
+use anchor_lang::prelude::*;
+
+use crate::decimal::{round_quotient, RoundingMode};
+use crate::ErrorCode;
+
+const LOW_MASK: u128 = u64::MAX as u128;
+
+/// Full 256-bit product of two u128 values as (high, low) halves
+pub fn full_mul_u128(a: u128, b: u128) -> (u128, u128) {
+    let (a_lo, a_hi) = (a & LOW_MASK, a >> 64);
+    let (b_lo, b_hi) = (b & LOW_MASK, b >> 64);
+
+    let lo_lo = a_lo * b_lo;
+    let lo_hi = a_lo * b_hi;
+    let hi_lo = a_hi * b_lo;
+    let hi_hi = a_hi * b_hi;
+
+    let mid = (lo_lo >> 64) + (lo_hi & LOW_MASK) + (hi_lo & LOW_MASK);
+    let low = (lo_lo & LOW_MASK) | (mid << 64);
+    let high = hi_hi + (lo_hi >> 64) + (hi_lo >> 64) + (mid >> 64);
+    (high, low)
+}
+
+/// Divide a 256-bit value by `divisor`, returning (quotient, remainder).
+/// Returns `None` if the divisor is zero or the quotient does not fit in u128.
+pub fn div_wide_u128(high: u128, low: u128, divisor: u128) -> Option<(u128, u128)> {
+    if divisor == 0 || high >= divisor {
+        return None;
+    }
+    let mut remainder = high;
+    let mut quotient = 0u128;
+    for bit in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((low >> bit) & 1);
+        quotient <<= 1;
+        if carry == 1 || remainder >= divisor {
+            remainder = remainder.wrapping_sub(divisor);
+            quotient |= 1;
+        }
+    }
+    Some((quotient, remainder))
+}
+
+/// `a * b / c` rounded down, using a 256-bit intermediate product
+pub fn mul_div_u128(a: u128, b: u128, c: u128) -> Option<u128> {
+    let (high, low) = full_mul_u128(a, b);
+    div_wide_u128(high, low, c).map(|(quotient, _)| quotient)
+}
+
+/// `a * b / c` with a u128 intermediate product, rounded according to `rounding`.
+/// Fails only if `c` is zero or the rounded result does not fit in a u64.
+pub fn mul_div_u64(a: u64, b: u64, c: u64, rounding: RoundingMode) -> Result<u64> {
+    let product = a as u128 * b as u128;
+    if c == 0 {
+        return Err(ErrorCode::DivideByZero.with_operands(product, c));
+    }
+    let quotient = round_quotient(
+        product / c as u128,
+        product % c as u128,
+        (0, c as u128),
+        rounding,
+    )?;
+    u64::try_from(quotient).map_err(|_| ErrorCode::Overflow.with_operands(a, b))
+}
+
+/// Square root rounded down
+pub fn isqrt(value: u64) -> u64 {
+    if value < 2 {
+        return value;
+    }
+    // Newton's method, starting from a power of two at or above the root
+    let bits = u64::BITS - value.leading_zeros();
+    let mut root = 1u64 << bits.div_ceil(2);
+    loop {
+        let next = (root + value / root) / 2;
+        if next >= root {
+            return root;
+        }
+        root = next;
+    }
+}
+
+/// `n`th root rounded down, or `None` if `n` is zero
+pub fn nth_root(value: u64, n: u32) -> Option<u64> {
+    if n == 0 {
+        return None;
+    }
+    // The root has at most ceil(64 / n) bits; set them from the top down
+    let top_bit = (u64::BITS.div_ceil(n) - 1).min(u64::BITS - 1);
+    let mut root = 0u64;
+    for bit in (0..=top_bit).rev() {
+        let candidate = root | (1 << bit);
+        if candidate.checked_pow(n).is_some_and(|power| power <= value) {
+            root = candidate;
+        }
+    }
+    Some(root)
+}
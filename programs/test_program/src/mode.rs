@@ -0,0 +1,48 @@
+//! Selectable overflow behaviour for the basic u64 operations
+
+use anchor_lang::prelude::*;
+
+use crate::ErrorCode;
+
+/// What to do when a result does not fit in a u64
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArithmeticMode {
+    /// Fail with `Overflow` or `Underflow`
+    Checked,
+    /// Clamp to `0` or `u64::MAX`
+    Saturating,
+    /// Wrap around modulo 2^64
+    Wrapping,
+}
+
+impl ArithmeticMode {
+    pub fn add(self, a: u64, b: u64) -> Result<u64> {
+        match self {
+            Self::Checked => a
+                .checked_add(b)
+                .ok_or_else(|| ErrorCode::Overflow.with_operands(a, b)),
+            Self::Saturating => Ok(a.saturating_add(b)),
+            Self::Wrapping => Ok(a.wrapping_add(b)),
+        }
+    }
+
+    pub fn sub(self, a: u64, b: u64) -> Result<u64> {
+        match self {
+            Self::Checked => a
+                .checked_sub(b)
+                .ok_or_else(|| ErrorCode::Underflow.with_operands(a, b)),
+            Self::Saturating => Ok(a.saturating_sub(b)),
+            Self::Wrapping => Ok(a.wrapping_sub(b)),
+        }
+    }
+
+    pub fn mul(self, a: u64, b: u64) -> Result<u64> {
+        match self {
+            Self::Checked => a
+                .checked_mul(b)
+                .ok_or_else(|| ErrorCode::Overflow.with_operands(a, b)),
+            Self::Saturating => Ok(a.saturating_mul(b)),
+            Self::Wrapping => Ok(a.wrapping_mul(b)),
+        }
+    }
+}
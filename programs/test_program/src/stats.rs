@@ -0,0 +1,72 @@
+//! Aggregate statistics over a small list of u64 values
+
+use anchor_lang::prelude::*;
+
+use crate::math::{div_wide_u128, full_mul_u128};
+use crate::ErrorCode;
+
+/// Upper bound on the number of values accepted by `stats`
+pub const MAX_STATS_LEN: usize = 64;
+
+/// Summary of a list of values. Mean, median and variance are rounded down.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Stats {
+    pub sum: u128,
+    pub mean: u64,
+    /// Middle value, or the mean of the two middle values for an even count
+    pub median: u64,
+    pub min: u64,
+    pub max: u64,
+    /// Population variance
+    pub variance: u128,
+}
+
+/// Compute `Stats` for between one and `MAX_STATS_LEN` values
+pub fn compute(mut values: Vec<u64>) -> Result<Stats> {
+    if values.len() > MAX_STATS_LEN {
+        return Err(ErrorCode::TooManyElements.with_operands(values.len(), MAX_STATS_LEN));
+    }
+    if values.is_empty() {
+        return Err(ErrorCode::InvalidOperand.with_operands(0, MAX_STATS_LEN));
+    }
+    values.sort_unstable();
+    let count = values.len() as u128;
+
+    // Sum and sum of squares; the latter needs up to 134 bits, kept as (high, low)
+    let mut sum = 0u128;
+    let (mut squares_high, mut squares_low) = (0u128, 0u128);
+    for &value in &values {
+        sum = sum
+            .checked_add(value as u128)
+            .ok_or_else(|| ErrorCode::Overflow.with_operands(sum, value))?;
+        let (low, carry) = squares_low.overflowing_add(value as u128 * value as u128);
+        squares_low = low;
+        squares_high += carry as u128;
+    }
+
+    // Variance = (count * sum of squares - sum^2) / count^2
+    let (scaled_high, scaled_low) = full_mul_u128(squares_low, count);
+    let scaled_high = scaled_high + squares_high * count;
+    let (sum_squared_high, sum_squared_low) = full_mul_u128(sum, sum);
+    let (low, borrow) = scaled_low.overflowing_sub(sum_squared_low);
+    let high = scaled_high - sum_squared_high - borrow as u128;
+    let (variance, _) = div_wide_u128(high, low, count * count)
+        .ok_or_else(|| ErrorCode::Overflow.with_operands(sum, count))?;
+
+    let middle = values.len() / 2;
+    let median = if values.len() % 2 == 1 {
+        values[middle]
+    } else {
+        let (a, b) = (values[middle - 1], values[middle]);
+        (a & b) + ((a ^ b) >> 1)
+    };
+
+    Ok(Stats {
+        sum,
+        mean: (sum / count) as u64,
+        median,
+        min: values[0],
+        max: values[values.len() - 1],
+        variance,
+    })
+}
@@ -0,0 +1,283 @@
+//! Shared LiteSVM harness for the integration tests
+//!
+//! The tests load `target/deploy/test_program.so`, so run `anchor build`
+//! (or `cargo build-sbf`) before `cargo test`.
+
+#![allow(dead_code)]
+
+use anchor_lang::{
+    solana_program::bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    AnchorDeserialize, Event, InstructionData, ToAccountMetas,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use litesvm::LiteSVM;
+use solana_sdk::{
+    account::Account,
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use test_program::{accounts, Accumulator, Config, History};
+
+pub const PROGRAM_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../../target/deploy/test_program.so"
+);
+
+pub struct TestContext {
+    pub svm: LiteSVM,
+    /// Fee payer, and upgrade authority of the deployed program
+    pub payer: Keypair,
+    /// Seed of the next keypair handed out by `new_user`
+    next_seed: u8,
+}
+
+impl TestContext {
+    pub fn new() -> Self {
+        let mut svm = LiteSVM::new();
+        let payer = seeded_keypair(0);
+        svm.airdrop(&payer.pubkey(), 100_000_000_000).unwrap();
+        deploy_upgradeable(&mut svm, &payer.pubkey());
+        Self {
+            svm,
+            payer,
+            next_seed: 1,
+        }
+    }
+
+    /// Create a funded keypair
+    pub fn new_user(&mut self) -> Keypair {
+        let user = seeded_keypair(self.next_seed);
+        self.next_seed += 1;
+        self.svm.airdrop(&user.pubkey(), 10_000_000_000).unwrap();
+        user
+    }
+
+    fn transaction(&mut self, instruction: Instruction, signers: &[&Keypair]) -> Transaction {
+        // Identical transactions would otherwise be rejected as already processed
+        self.svm.expire_blockhash();
+        let mut all_signers = vec![&self.payer];
+        all_signers.extend_from_slice(signers);
+        Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&self.payer.pubkey()),
+            &all_signers,
+            self.svm.latest_blockhash(),
+        )
+    }
+
+    /// Send `instruction` signed by the payer and `signers`, decoding its return data
+    pub fn send<T: AnchorDeserialize>(
+        &mut self,
+        instruction: Instruction,
+        signers: &[&Keypair],
+    ) -> Result<T, u32> {
+        self.send_with_logs(instruction, signers).0
+    }
+
+    /// Like `send`, but also return the transaction logs
+    pub fn send_with_logs<T: AnchorDeserialize>(
+        &mut self,
+        instruction: Instruction,
+        signers: &[&Keypair],
+    ) -> (Result<T, u32>, Vec<String>) {
+        let tx = self.transaction(instruction, signers);
+        match self.svm.send_transaction(tx) {
+            Ok(meta) => (Ok(decode_return_data(&meta.return_data.data)), meta.logs),
+            Err(failed) => match failed.err {
+                TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+                    (Err(code), failed.meta.logs)
+                }
+                err => panic!(
+                    "unexpected transaction error {err:?}: {:#?}",
+                    failed.meta.logs
+                ),
+            },
+        }
+    }
+
+    /// Send `instruction` and return the compute units it consumed
+    pub fn measure(&mut self, instruction: Instruction, signers: &[&Keypair]) -> u64 {
+        let tx = self.transaction(instruction, signers);
+        match self.svm.send_transaction(tx) {
+            Ok(meta) => meta.compute_units_consumed,
+            Err(failed) => panic!("{:?}: {:#?}", failed.err, failed.meta.logs),
+        }
+    }
+
+    /// Call an arithmetic instruction with the default `Add` accounts
+    pub fn call<T: AnchorDeserialize>(&mut self, data: impl InstructionData) -> Result<T, u32> {
+        self.call_as(data, None, None)
+    }
+
+    /// Call an arithmetic instruction with an optional caller and history account
+    pub fn call_as<T: AnchorDeserialize>(
+        &mut self,
+        data: impl InstructionData,
+        caller: Option<&Keypair>,
+        history: Option<Pubkey>,
+    ) -> Result<T, u32> {
+        let instruction = arithmetic_ix(data, caller.map(|caller| caller.pubkey()), history);
+        let signers: Vec<&Keypair> = caller.into_iter().collect();
+        self.send(instruction, &signers)
+    }
+
+    /// Create the config PDA with the payer as admin
+    pub fn initialize_config(&mut self) {
+        let accounts = accounts::InitializeConfig {
+            config: config_address(),
+            admin: self.payer.pubkey(),
+            program: test_program::ID,
+            program_data: program_data_address(),
+            system_program: solana_sdk::system_program::ID,
+        };
+        let ix = instruction(test_program::instruction::InitializeConfig {}, accounts);
+        self.send::<()>(ix, &[]).unwrap();
+    }
+
+    /// Run an admin instruction signed by `admin`
+    pub fn update_config(
+        &mut self,
+        data: impl InstructionData,
+        admin: &Keypair,
+    ) -> Result<(), u32> {
+        let accounts = accounts::UpdateConfig {
+            config: config_address(),
+            admin: admin.pubkey(),
+        };
+        let ix = instruction(data, accounts);
+        self.send(ix, &[admin])
+    }
+
+    pub fn config(&self) -> Config {
+        self.read_account(&config_address())
+    }
+
+    pub fn accumulator(&self, authority: &Pubkey) -> Accumulator {
+        self.read_account(&accumulator_address(authority))
+    }
+
+    pub fn history(&self, authority: &Pubkey) -> History {
+        let account = self.svm.get_account(&history_address(authority)).unwrap();
+        bytemuck::pod_read_unaligned(&account.data[8..8 + std::mem::size_of::<History>()])
+    }
+
+    fn read_account<T: anchor_lang::AccountDeserialize>(&self, address: &Pubkey) -> T {
+        let account = self.svm.get_account(address).unwrap();
+        T::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+}
+
+pub fn instruction(data: impl InstructionData, accounts: impl ToAccountMetas) -> Instruction {
+    Instruction {
+        program_id: test_program::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Build an instruction that takes the `Add` accounts
+pub fn arithmetic_ix(
+    data: impl InstructionData,
+    caller: Option<Pubkey>,
+    history: Option<Pubkey>,
+) -> Instruction {
+    let accounts = accounts::Add {
+        config: config_address(),
+        caller,
+        history,
+    };
+    instruction(data, accounts)
+}
+
+/// Anchor error code as reported in `InstructionError::Custom`
+pub fn error_code(error: test_program::ErrorCode) -> u32 {
+    error.into()
+}
+
+pub fn config_address() -> Pubkey {
+    Pubkey::find_program_address(&[Config::SEED], &test_program::ID).0
+}
+
+pub fn accumulator_address(authority: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[Accumulator::SEED, authority.as_ref()], &test_program::ID).0
+}
+
+pub fn history_address(authority: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[History::SEED, authority.as_ref()], &test_program::ID).0
+}
+
+pub fn program_data_address() -> Pubkey {
+    Pubkey::find_program_address(&[test_program::ID.as_ref()], &bpf_loader_upgradeable::ID).0
+}
+
+/// Decode the events of type `T` logged by `emit!`
+pub fn events<T: Event>(logs: &[String]) -> Vec<T> {
+    logs.iter()
+        .filter_map(|line| line.strip_prefix("Program data: "))
+        .map(|encoded| STANDARD.decode(encoded).unwrap())
+        .filter_map(|data| {
+            let payload = data.strip_prefix(T::DISCRIMINATOR)?;
+            Some(T::deserialize(&mut &payload[..]).unwrap())
+        })
+        .collect()
+}
+
+/// Keys come from fixed seeds so PDA bump searches, and with them compute
+/// unit costs, are the same on every run
+fn seeded_keypair(seed: u8) -> Keypair {
+    Keypair::new_from_array([seed; 32])
+}
+
+/// The runtime drops trailing zero bytes from return data, so pad before decoding
+fn decode_return_data<T: AnchorDeserialize>(data: &[u8]) -> T {
+    let mut padded = data.to_vec();
+    padded.resize(data.len() + 1024, 0);
+    T::deserialize(&mut padded.as_slice()).unwrap()
+}
+
+/// Install the program behind the upgradeable loader with `authority` as its
+/// upgrade authority, which `initialize_config` checks for.
+fn deploy_upgradeable(svm: &mut LiteSVM, authority: &Pubkey) {
+    let elf = std::fs::read(PROGRAM_PATH).unwrap_or_else(|err| {
+        panic!("failed to read {PROGRAM_PATH}: {err}; run `anchor build` first")
+    });
+    let rent = Rent::default();
+
+    let mut program_data = bincode::serialize(&UpgradeableLoaderState::ProgramData {
+        slot: 0,
+        upgrade_authority_address: Some(*authority),
+    })
+    .unwrap();
+    program_data.resize(UpgradeableLoaderState::size_of_programdata_metadata(), 0);
+    program_data.extend_from_slice(&elf);
+    svm.set_account(
+        program_data_address(),
+        Account {
+            lamports: rent.minimum_balance(program_data.len()),
+            data: program_data,
+            owner: bpf_loader_upgradeable::ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    )
+    .unwrap();
+
+    let program = bincode::serialize(&UpgradeableLoaderState::Program {
+        programdata_address: program_data_address(),
+    })
+    .unwrap();
+    svm.set_account(
+        test_program::ID,
+        Account {
+            lamports: rent.minimum_balance(program.len()),
+            data: program,
+            owner: bpf_loader_upgradeable::ID,
+            executable: true,
+            rent_epoch: 0,
+        },
+    )
+    .unwrap();
+}
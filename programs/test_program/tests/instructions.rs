@@ -0,0 +1,944 @@
+mod common;
+
+use anchor_lang::InstructionData;
+use common::{error_code, TestContext};
+use solana_sdk::signature::{Keypair, Signer};
+use test_program::{
+    decimal::Decimal, evaluator::opcode, instruction as ix, stats, ArithmeticEvent, ArithmeticMode,
+    DivRem, ErrorCode, Operand, Operation, OperationKind, RoundingMode, Stats,
+};
+
+#[test]
+fn add() {
+    let mut ctx = TestContext::new();
+    assert_eq!(ctx.call::<u64>(ix::Add { a: 1, b: 2 }), Ok(3));
+    assert_eq!(
+        ctx.call::<u64>(ix::Add { a: u64::MAX, b: 1 }),
+        Err(error_code(ErrorCode::Overflow))
+    );
+}
+
+#[test]
+fn add_emits_event() {
+    let mut ctx = TestContext::new();
+    let caller = ctx.new_user();
+    let add = common::arithmetic_ix(ix::Add { a: 2, b: 3 }, Some(caller.pubkey()), None);
+    let (result, logs) = ctx.send_with_logs::<u64>(add, &[&caller]);
+    assert_eq!(result, Ok(5));
+
+    let events = common::events::<ArithmeticEvent>(&logs);
+    assert_eq!(events.len(), 1);
+    let event = &events[0];
+    assert_eq!(event.operation, OperationKind::Add);
+    assert_eq!((event.a, event.b, event.result), (2, 3, 5));
+    assert_eq!(event.caller, Some(caller.pubkey()));
+}
+
+#[test]
+fn subtract() {
+    let mut ctx = TestContext::new();
+    assert_eq!(ctx.call::<u64>(ix::Subtract { a: 5, b: 3 }), Ok(2));
+    assert_eq!(
+        ctx.call::<u64>(ix::Subtract { a: 3, b: 5 }),
+        Err(error_code(ErrorCode::Underflow))
+    );
+}
+
+#[test]
+fn multiply() {
+    let mut ctx = TestContext::new();
+    assert_eq!(ctx.call::<u64>(ix::Multiply { a: 6, b: 7 }), Ok(42));
+    assert_eq!(
+        ctx.call::<u64>(ix::Multiply { a: u64::MAX, b: 2 }),
+        Err(error_code(ErrorCode::Overflow))
+    );
+}
+
+#[test]
+fn arithmetic_modes() {
+    let mut ctx = TestContext::new();
+    for mode in [
+        ArithmeticMode::Checked,
+        ArithmeticMode::Saturating,
+        ArithmeticMode::Wrapping,
+    ] {
+        assert_eq!(ctx.call::<u64>(ix::AddWithMode { a: 1, b: 2, mode }), Ok(3));
+        assert_eq!(
+            ctx.call::<u64>(ix::SubtractWithMode { a: 5, b: 3, mode }),
+            Ok(2)
+        );
+        assert_eq!(
+            ctx.call::<u64>(ix::MultiplyWithMode { a: 6, b: 7, mode }),
+            Ok(42)
+        );
+    }
+
+    let mode = ArithmeticMode::Checked;
+    assert_eq!(
+        ctx.call::<u64>(ix::AddWithMode {
+            a: u64::MAX,
+            b: 1,
+            mode
+        }),
+        Err(error_code(ErrorCode::Overflow))
+    );
+    assert_eq!(
+        ctx.call::<u64>(ix::SubtractWithMode { a: 0, b: 1, mode }),
+        Err(error_code(ErrorCode::Underflow))
+    );
+    assert_eq!(
+        ctx.call::<u64>(ix::MultiplyWithMode {
+            a: u64::MAX,
+            b: 2,
+            mode
+        }),
+        Err(error_code(ErrorCode::Overflow))
+    );
+
+    let mode = ArithmeticMode::Saturating;
+    assert_eq!(
+        ctx.call::<u64>(ix::AddWithMode {
+            a: u64::MAX,
+            b: 1,
+            mode
+        }),
+        Ok(u64::MAX)
+    );
+    assert_eq!(
+        ctx.call::<u64>(ix::SubtractWithMode { a: 0, b: 1, mode }),
+        Ok(0)
+    );
+    assert_eq!(
+        ctx.call::<u64>(ix::MultiplyWithMode {
+            a: u64::MAX,
+            b: 2,
+            mode
+        }),
+        Ok(u64::MAX)
+    );
+
+    let mode = ArithmeticMode::Wrapping;
+    assert_eq!(
+        ctx.call::<u64>(ix::AddWithMode {
+            a: u64::MAX,
+            b: 1,
+            mode
+        }),
+        Ok(0)
+    );
+    assert_eq!(
+        ctx.call::<u64>(ix::SubtractWithMode { a: 0, b: 1, mode }),
+        Ok(u64::MAX)
+    );
+    assert_eq!(
+        ctx.call::<u64>(ix::MultiplyWithMode {
+            a: u64::MAX,
+            b: 2,
+            mode
+        }),
+        Ok(u64::MAX - 1)
+    );
+}
+
+#[test]
+fn max() {
+    let mut ctx = TestContext::new();
+    assert_eq!(ctx.call::<u64>(ix::Max { a: 6, b: 7 }), Ok(7));
+    assert_eq!(ctx.call::<u64>(ix::Max { a: u64::MAX, b: 0 }), Ok(u64::MAX));
+}
+
+#[test]
+fn min() {
+    let mut ctx = TestContext::new();
+    assert_eq!(ctx.call::<u64>(ix::Min { a: 6, b: 7 }), Ok(6));
+    assert_eq!(ctx.call::<u64>(ix::Min { a: u64::MAX, b: 0 }), Ok(0));
+}
+
+#[test]
+fn clamp() {
+    let mut ctx = TestContext::new();
+    let clamp = |ctx: &mut TestContext, value, lo, hi| ctx.call::<u64>(ix::Clamp { value, lo, hi });
+    assert_eq!(clamp(&mut ctx, 3, 5, 10), Ok(5));
+    assert_eq!(clamp(&mut ctx, 7, 5, 10), Ok(7));
+    assert_eq!(clamp(&mut ctx, 12, 5, 10), Ok(10));
+    assert_eq!(clamp(&mut ctx, 12, 5, 5), Ok(5));
+    assert_eq!(clamp(&mut ctx, u64::MAX, 0, u64::MAX), Ok(u64::MAX));
+    assert_eq!(
+        clamp(&mut ctx, 7, 10, 5),
+        Err(error_code(ErrorCode::InvalidRange))
+    );
+}
+
+#[test]
+fn abs_diff_and_midpoint() {
+    let mut ctx = TestContext::new();
+    assert_eq!(ctx.call::<u64>(ix::AbsDiff { a: 3, b: 10 }), Ok(7));
+    assert_eq!(ctx.call::<u64>(ix::AbsDiff { a: 10, b: 3 }), Ok(7));
+    assert_eq!(
+        ctx.call::<u64>(ix::AbsDiff { a: 0, b: u64::MAX }),
+        Ok(u64::MAX)
+    );
+
+    assert_eq!(ctx.call::<u64>(ix::Midpoint { a: 3, b: 10 }), Ok(6));
+    assert_eq!(ctx.call::<u64>(ix::Midpoint { a: 10, b: 3 }), Ok(6));
+    assert_eq!(
+        ctx.call::<u64>(ix::Midpoint {
+            a: u64::MAX,
+            b: u64::MAX
+        }),
+        Ok(u64::MAX)
+    );
+    assert_eq!(
+        ctx.call::<u64>(ix::Midpoint { a: u64::MAX, b: 0 }),
+        Ok(u64::MAX / 2)
+    );
+}
+
+#[test]
+fn division() {
+    let mut ctx = TestContext::new();
+    assert_eq!(ctx.call::<u64>(ix::Divide { a: 7, b: 2 }), Ok(3));
+    assert_eq!(ctx.call::<u64>(ix::Modulo { a: 7, b: 2 }), Ok(1));
+    assert_eq!(ctx.call::<u64>(ix::DivCeil { a: 7, b: 2 }), Ok(4));
+    assert_eq!(
+        ctx.call::<DivRem>(ix::DivRem { a: 7, b: 2 }),
+        Ok(DivRem {
+            quotient: 3,
+            remainder: 1
+        })
+    );
+
+    let divide_by_zero = Err(error_code(ErrorCode::DivideByZero));
+    assert_eq!(ctx.call::<u64>(ix::Divide { a: 7, b: 0 }), divide_by_zero);
+    assert_eq!(ctx.call::<u64>(ix::Modulo { a: 7, b: 0 }), divide_by_zero);
+    assert_eq!(ctx.call::<u64>(ix::DivCeil { a: 7, b: 0 }), divide_by_zero);
+    assert_eq!(
+        ctx.call::<DivRem>(ix::DivRem { a: 7, b: 0 }),
+        Err(error_code(ErrorCode::DivideByZero))
+    );
+}
+
+#[test]
+fn roots_powers_and_logs() {
+    let mut ctx = TestContext::new();
+    let invalid_operand = error_code(ErrorCode::InvalidOperand);
+
+    assert_eq!(ctx.call::<u64>(ix::Isqrt { value: 0 }), Ok(0));
+    assert_eq!(ctx.call::<u64>(ix::Isqrt { value: 15 }), Ok(3));
+    assert_eq!(ctx.call::<u64>(ix::Isqrt { value: 16 }), Ok(4));
+    assert_eq!(
+        ctx.call::<u64>(ix::Isqrt { value: u64::MAX }),
+        Ok(u32::MAX as u64)
+    );
+
+    assert_eq!(ctx.call::<u64>(ix::CheckedPow { base: 0, exp: 0 }), Ok(1));
+    assert_eq!(ctx.call::<u64>(ix::CheckedPow { base: 0, exp: 5 }), Ok(0));
+    assert_eq!(
+        ctx.call::<u64>(ix::CheckedPow { base: 2, exp: 63 }),
+        Ok(1 << 63)
+    );
+    assert_eq!(
+        ctx.call::<u64>(ix::CheckedPow { base: 2, exp: 64 }),
+        Err(error_code(ErrorCode::Overflow))
+    );
+    assert_eq!(
+        ctx.call::<u64>(ix::CheckedPow {
+            base: u64::MAX,
+            exp: 1
+        }),
+        Ok(u64::MAX)
+    );
+    assert_eq!(
+        ctx.call::<u64>(ix::CheckedPow {
+            base: u64::MAX,
+            exp: 2
+        }),
+        Err(error_code(ErrorCode::Overflow))
+    );
+
+    assert_eq!(ctx.call::<u32>(ix::Ilog2 { value: 1 }), Ok(0));
+    assert_eq!(ctx.call::<u32>(ix::Ilog2 { value: u64::MAX }), Ok(63));
+    assert_eq!(
+        ctx.call::<u32>(ix::Ilog2 { value: 0 }),
+        Err(invalid_operand)
+    );
+    assert_eq!(ctx.call::<u32>(ix::Ilog10 { value: 9 }), Ok(0));
+    assert_eq!(ctx.call::<u32>(ix::Ilog10 { value: 10 }), Ok(1));
+    assert_eq!(ctx.call::<u32>(ix::Ilog10 { value: u64::MAX }), Ok(19));
+    assert_eq!(
+        ctx.call::<u32>(ix::Ilog10 { value: 0 }),
+        Err(invalid_operand)
+    );
+
+    assert_eq!(ctx.call::<u64>(ix::NthRoot { value: 0, n: 3 }), Ok(0));
+    assert_eq!(ctx.call::<u64>(ix::NthRoot { value: 26, n: 3 }), Ok(2));
+    assert_eq!(ctx.call::<u64>(ix::NthRoot { value: 27, n: 3 }), Ok(3));
+    assert_eq!(
+        ctx.call::<u64>(ix::NthRoot {
+            value: u64::MAX,
+            n: 1
+        }),
+        Ok(u64::MAX)
+    );
+    assert_eq!(
+        ctx.call::<u64>(ix::NthRoot {
+            value: u64::MAX,
+            n: 3
+        }),
+        Ok(2_642_245)
+    );
+    assert_eq!(
+        ctx.call::<u64>(ix::NthRoot {
+            value: u64::MAX,
+            n: 64
+        }),
+        Ok(1)
+    );
+    assert_eq!(
+        ctx.call::<u64>(ix::NthRoot {
+            value: u64::MAX,
+            n: u32::MAX
+        }),
+        Ok(1)
+    );
+    assert_eq!(
+        ctx.call::<u64>(ix::NthRoot { value: 8, n: 0 }),
+        Err(invalid_operand)
+    );
+}
+
+#[test]
+fn mul_div() {
+    let mut ctx = TestContext::new();
+    let calls = |ctx: &mut TestContext, a, b, c| {
+        (
+            ctx.call::<u64>(ix::MulDivFloor { a, b, c }),
+            ctx.call::<u64>(ix::MulDivCeil { a, b, c }),
+            ctx.call::<u64>(ix::MulDivRound { a, b, c }),
+        )
+    };
+    assert_eq!(calls(&mut ctx, 10, 3, 5), (Ok(6), Ok(6), Ok(6)));
+    assert_eq!(calls(&mut ctx, 10, 1, 4), (Ok(2), Ok(3), Ok(2)));
+    assert_eq!(calls(&mut ctx, 14, 1, 4), (Ok(3), Ok(4), Ok(4)));
+    assert_eq!(calls(&mut ctx, 11, 1, 4), (Ok(2), Ok(3), Ok(3)));
+    assert_eq!(calls(&mut ctx, 0, u64::MAX, 1), (Ok(0), Ok(0), Ok(0)));
+
+    // The intermediate product overflows u64 but the result does not
+    let max = u64::MAX;
+    assert_eq!(calls(&mut ctx, max, max, max), (Ok(max), Ok(max), Ok(max)));
+    assert_eq!(
+        calls(&mut ctx, max, 3, 4),
+        (
+            Ok(max / 4 * 3 + 2),
+            Ok(max / 4 * 3 + 3),
+            Ok(max / 4 * 3 + 2)
+        )
+    );
+    let overflow = Err(error_code(ErrorCode::Overflow));
+    assert_eq!(calls(&mut ctx, max, 2, 1), (overflow, overflow, overflow));
+    // 31 * 1190112520884487201 = 2 * u64::MAX + 1, which only fits rounded down
+    assert_eq!(
+        calls(&mut ctx, 31, 1_190_112_520_884_487_201, 2),
+        (Ok(max), overflow, overflow)
+    );
+
+    let divide_by_zero = Err(error_code(ErrorCode::DivideByZero));
+    assert_eq!(
+        calls(&mut ctx, 1, 2, 0),
+        (divide_by_zero, divide_by_zero, divide_by_zero)
+    );
+}
+
+#[test]
+fn unsigned_128() {
+    let mut ctx = TestContext::new();
+    let big = u64::MAX as u128;
+    assert_eq!(ctx.call::<u128>(ix::AddU128 { a: big, b: 1 }), Ok(big + 1));
+    assert_eq!(
+        ctx.call::<u128>(ix::SubU128 { a: 1, b: big }),
+        Err(error_code(ErrorCode::Underflow))
+    );
+    assert_eq!(
+        ctx.call::<u128>(ix::MulU128 { a: big, b: big }),
+        Ok(big * big)
+    );
+    assert_eq!(
+        ctx.call::<u128>(ix::MulU128 { a: u128::MAX, b: 2 }),
+        Err(error_code(ErrorCode::Overflow))
+    );
+    assert_eq!(ctx.call::<u128>(ix::DivU128 { a: big * 4, b: 4 }), Ok(big));
+    assert_eq!(
+        ctx.call::<u128>(ix::DivU128 { a: 1, b: 0 }),
+        Err(error_code(ErrorCode::DivideByZero))
+    );
+    assert_eq!(
+        ctx.call::<u128>(ix::MulDivU128 {
+            a: u128::MAX,
+            b: 3,
+            c: 4
+        }),
+        Ok(u128::MAX / 4 * 3 + 2)
+    );
+    assert_eq!(
+        ctx.call::<u128>(ix::MulDivU128 {
+            a: u128::MAX,
+            b: 2,
+            c: 1
+        }),
+        Err(error_code(ErrorCode::Overflow))
+    );
+    assert_eq!(
+        ctx.call::<u128>(ix::MulDivU128 { a: 1, b: 2, c: 0 }),
+        Err(error_code(ErrorCode::DivideByZero))
+    );
+}
+
+#[test]
+fn signed() {
+    let mut ctx = TestContext::new();
+    assert_eq!(ctx.call::<i64>(ix::AddI64 { a: -5, b: 3 }), Ok(-2));
+    assert_eq!(
+        ctx.call::<i64>(ix::AddI64 { a: i64::MIN, b: -1 }),
+        Err(error_code(ErrorCode::Underflow))
+    );
+    assert_eq!(
+        ctx.call::<i64>(ix::SubI64 { a: i64::MAX, b: -1 }),
+        Err(error_code(ErrorCode::Overflow))
+    );
+    assert_eq!(ctx.call::<i64>(ix::MulI64 { a: -4, b: 5 }), Ok(-20));
+    assert_eq!(
+        ctx.call::<i64>(ix::MulI64 { a: i64::MAX, b: -2 }),
+        Err(error_code(ErrorCode::Underflow))
+    );
+
+    assert_eq!(
+        ctx.call::<i128>(ix::AddI128 { a: i128::MAX, b: 1 }),
+        Err(error_code(ErrorCode::Overflow))
+    );
+    assert_eq!(
+        ctx.call::<i128>(ix::SubI128 {
+            a: -1,
+            b: i128::MAX
+        }),
+        Ok(i128::MIN)
+    );
+    assert_eq!(
+        ctx.call::<i128>(ix::MulI128 {
+            a: i128::MIN,
+            b: -1
+        }),
+        Err(error_code(ErrorCode::Overflow))
+    );
+}
+
+#[test]
+fn decimal() {
+    let mut ctx = TestContext::new();
+    let d = |mantissa, scale| Decimal { mantissa, scale };
+
+    assert_eq!(
+        ctx.call::<Decimal>(ix::DecimalAdd {
+            a: d(150, 2),
+            b: d(25, 1),
+            scale: 2,
+            rounding: RoundingMode::Strict
+        }),
+        Ok(d(400, 2))
+    );
+    assert_eq!(
+        ctx.call::<Decimal>(ix::DecimalSub {
+            a: d(1, 0),
+            b: d(2, 0),
+            scale: 0,
+            rounding: RoundingMode::Floor
+        }),
+        Err(error_code(ErrorCode::Underflow))
+    );
+    assert_eq!(
+        ctx.call::<Decimal>(ix::DecimalMul {
+            a: d(25, 1),
+            b: d(15, 1),
+            scale: 1,
+            rounding: RoundingMode::HalfEven
+        }),
+        Ok(d(38, 1))
+    );
+    assert_eq!(
+        ctx.call::<Decimal>(ix::DecimalMul {
+            a: d(15, 1),
+            b: d(15, 1),
+            scale: 1,
+            rounding: RoundingMode::Strict
+        }),
+        Err(error_code(ErrorCode::PrecisionLoss))
+    );
+    assert_eq!(
+        ctx.call::<Decimal>(ix::DecimalDiv {
+            a: d(2, 0),
+            b: d(3, 0),
+            scale: 4,
+            rounding: RoundingMode::Ceil
+        }),
+        Ok(d(6667, 4))
+    );
+    assert_eq!(
+        ctx.call::<Decimal>(ix::DecimalDiv {
+            a: d(2, 0),
+            b: d(0, 0),
+            scale: 4,
+            rounding: RoundingMode::Ceil
+        }),
+        Err(error_code(ErrorCode::DivideByZero))
+    );
+    assert_eq!(
+        ctx.call::<Decimal>(ix::DecimalAdd {
+            a: d(1, 19),
+            b: d(1, 0),
+            scale: 0,
+            rounding: RoundingMode::Floor
+        }),
+        Err(error_code(ErrorCode::InvalidOperand))
+    );
+}
+
+#[test]
+fn stats() {
+    let mut ctx = TestContext::new();
+    assert_eq!(
+        ctx.call::<Stats>(ix::Stats {
+            values: vec![9, 2, 4, 4, 5, 4, 7, 5]
+        }),
+        Ok(Stats {
+            sum: 40,
+            mean: 5,
+            median: 4,
+            min: 2,
+            max: 9,
+            variance: 4,
+        })
+    );
+    assert_eq!(
+        ctx.call::<Stats>(ix::Stats {
+            values: vec![3, 1, 2]
+        }),
+        Ok(Stats {
+            sum: 6,
+            mean: 2,
+            median: 2,
+            min: 1,
+            max: 3,
+            variance: 0,
+        })
+    );
+
+    let max = u64::MAX as u128;
+    let extremes = [0, u64::MAX].repeat(stats::MAX_STATS_LEN / 2);
+    assert_eq!(
+        ctx.call::<Stats>(ix::Stats { values: extremes }),
+        Ok(Stats {
+            sum: max * stats::MAX_STATS_LEN as u128 / 2,
+            mean: u64::MAX / 2,
+            median: u64::MAX / 2,
+            min: 0,
+            max: u64::MAX,
+            variance: max * max / 4,
+        })
+    );
+
+    assert_eq!(
+        ctx.call::<Stats>(ix::Stats { values: vec![] }),
+        Err(error_code(ErrorCode::InvalidOperand))
+    );
+    assert_eq!(
+        ctx.call::<Stats>(ix::Stats {
+            values: vec![1; stats::MAX_STATS_LEN + 1]
+        }),
+        Err(error_code(ErrorCode::TooManyElements))
+    );
+}
+
+#[test]
+fn execute_batch() {
+    let mut ctx = TestContext::new();
+    let operations = vec![
+        Operation::Add(Operand::Value(2), Operand::Value(3)),
+        Operation::Multiply(Operand::Result(0), Operand::Value(4)),
+        Operation::Subtract(Operand::Result(1), Operand::Result(0)),
+        Operation::Max(Operand::Result(2), Operand::Value(10)),
+    ];
+    assert_eq!(
+        ctx.call::<Vec<u64>>(ix::ExecuteBatch { operations }),
+        Ok(vec![5, 20, 15, 15])
+    );
+
+    let operations = vec![
+        Operation::Add(Operand::Value(1), Operand::Value(1)),
+        Operation::Divide(Operand::Result(0), Operand::Value(0)),
+    ];
+    let (result, logs) = ctx.send_with_logs::<Vec<u64>>(
+        common::arithmetic_ix(ix::ExecuteBatch { operations }, None, None),
+        &[],
+    );
+    assert_eq!(result, Err(error_code(ErrorCode::BatchOperationFailed)));
+    assert_eq!(failed_batch_index(&logs), "1");
+    assert!(logs
+        .iter()
+        .any(|line| line.contains("Error Code: DivideByZero")));
+
+    let operations = vec![Operation::Add(Operand::Result(0), Operand::Value(1))];
+    let (result, logs) = ctx.send_with_logs::<Vec<u64>>(
+        common::arithmetic_ix(ix::ExecuteBatch { operations }, None, None),
+        &[],
+    );
+    assert_eq!(result, Err(error_code(ErrorCode::BatchOperationFailed)));
+    assert_eq!(failed_batch_index(&logs), "0");
+
+    let operations = vec![Operation::Max(Operand::Value(1), Operand::Value(2)); 65];
+    assert_eq!(
+        ctx.call::<Vec<u64>>(ix::ExecuteBatch { operations }),
+        Err(error_code(ErrorCode::BatchTooLarge))
+    );
+}
+
+/// Index reported by `BatchOperationFailed`, logged as the right-hand error value
+fn failed_batch_index(logs: &[String]) -> &str {
+    let left = logs
+        .iter()
+        .position(|line| line == "Program log: Left: index")
+        .expect("no batch index in logs");
+    logs[left + 1].strip_prefix("Program log: Right: ").unwrap()
+}
+
+fn push(program: &mut Vec<u8>, value: u64) {
+    program.push(opcode::PUSH);
+    program.extend_from_slice(&value.to_le_bytes());
+}
+
+#[test]
+fn evaluate() {
+    let mut ctx = TestContext::new();
+
+    // (2 + 3) * 4
+    let mut program = Vec::new();
+    push(&mut program, 2);
+    push(&mut program, 3);
+    program.push(opcode::ADD);
+    push(&mut program, 4);
+    program.push(opcode::MUL);
+    assert_eq!(ctx.call::<u64>(ix::Evaluate { program }), Ok(20));
+
+    assert_eq!(
+        ctx.call::<u64>(ix::Evaluate {
+            program: vec![opcode::ADD]
+        }),
+        Err(error_code(ErrorCode::StackUnderflow))
+    );
+
+    let mut program = Vec::new();
+    push(&mut program, 1);
+    program.extend(std::iter::repeat_n(opcode::DUP, 32));
+    assert_eq!(
+        ctx.call::<u64>(ix::Evaluate { program }),
+        Err(error_code(ErrorCode::StackOverflow))
+    );
+
+    assert_eq!(
+        ctx.call::<u64>(ix::Evaluate {
+            program: vec![opcode::DUP; 513]
+        }),
+        Err(error_code(ErrorCode::ProgramTooLong))
+    );
+    assert_eq!(
+        ctx.call::<u64>(ix::Evaluate {
+            program: vec![0xff]
+        }),
+        Err(error_code(ErrorCode::InvalidOperand))
+    );
+}
+
+#[test]
+fn caller_allowlist() {
+    let mut ctx = TestContext::new();
+    let admin = ctx.payer.insecure_clone();
+    let caller = ctx.new_user();
+    ctx.initialize_config();
+
+    ctx.update_config(ix::SetCallerRestriction { enabled: true }, &admin)
+        .unwrap();
+    assert_eq!(
+        ctx.call::<u64>(ix::Add { a: 1, b: 2 }),
+        Err(error_code(ErrorCode::Unauthorized))
+    );
+    assert_eq!(
+        ctx.call_as::<u64>(ix::Add { a: 1, b: 2 }, Some(&caller), None),
+        Err(error_code(ErrorCode::Unauthorized))
+    );
+
+    ctx.update_config(
+        ix::AddCaller {
+            caller: caller.pubkey(),
+        },
+        &admin,
+    )
+    .unwrap();
+    assert_eq!(
+        ctx.update_config(
+            ix::AddCaller {
+                caller: caller.pubkey()
+            },
+            &admin
+        ),
+        Err(error_code(ErrorCode::CallerAlreadyAllowed))
+    );
+    assert_eq!(
+        ctx.call_as::<u64>(ix::Add { a: 1, b: 2 }, Some(&caller), None),
+        Ok(3)
+    );
+
+    ctx.update_config(
+        ix::RemoveCaller {
+            caller: caller.pubkey(),
+        },
+        &admin,
+    )
+    .unwrap();
+    assert_eq!(
+        ctx.update_config(
+            ix::RemoveCaller {
+                caller: caller.pubkey()
+            },
+            &admin
+        ),
+        Err(error_code(ErrorCode::CallerNotAllowed))
+    );
+    assert_eq!(
+        ctx.update_config(ix::SetCallerRestriction { enabled: false }, &caller),
+        Err(error_code(ErrorCode::Unauthorized))
+    );
+}
+
+#[test]
+fn admin_transfer() {
+    let mut ctx = TestContext::new();
+    let admin = ctx.payer.insecure_clone();
+    let new_admin = ctx.new_user();
+    ctx.initialize_config();
+
+    assert_eq!(
+        ctx.update_config(ix::CancelAdminTransfer {}, &admin),
+        Err(error_code(ErrorCode::NoPendingAdmin))
+    );
+    let accept = common::instruction(
+        ix::AcceptAdmin {},
+        test_program::accounts::AcceptAdmin {
+            config: common::config_address(),
+            pending_admin: new_admin.pubkey(),
+        },
+    );
+
+    // A cancelled transfer can no longer be accepted
+    ctx.update_config(
+        ix::ProposeAdmin {
+            new_admin: new_admin.pubkey(),
+        },
+        &admin,
+    )
+    .unwrap();
+    ctx.update_config(ix::CancelAdminTransfer {}, &admin)
+        .unwrap();
+    assert_eq!(ctx.config().pending_admin, None);
+    assert_eq!(
+        ctx.send::<()>(accept.clone(), &[&new_admin]),
+        Err(error_code(ErrorCode::Unauthorized))
+    );
+
+    ctx.update_config(
+        ix::ProposeAdmin {
+            new_admin: new_admin.pubkey(),
+        },
+        &admin,
+    )
+    .unwrap();
+    assert_eq!(ctx.config().pending_admin, Some(new_admin.pubkey()));
+    ctx.send::<()>(accept, &[&new_admin]).unwrap();
+    let config = ctx.config();
+    assert_eq!(config.admin, new_admin.pubkey());
+    assert_eq!(config.pending_admin, None);
+    assert_eq!(
+        ctx.update_config(ix::Pause {}, &admin),
+        Err(error_code(ErrorCode::Unauthorized))
+    );
+}
+
+#[test]
+fn pause() {
+    let mut ctx = TestContext::new();
+    let admin = ctx.payer.insecure_clone();
+    ctx.initialize_config();
+
+    ctx.update_config(ix::Pause {}, &admin).unwrap();
+    assert_eq!(
+        ctx.call::<u64>(ix::Add { a: 1, b: 2 }),
+        Err(error_code(ErrorCode::ProgramPaused))
+    );
+    ctx.update_config(ix::Unpause {}, &admin).unwrap();
+    assert_eq!(ctx.call::<u64>(ix::Add { a: 1, b: 2 }), Ok(3));
+
+    ctx.update_config(
+        ix::SetOperationPaused {
+            operation: OperationKind::Multiply,
+            paused: true,
+        },
+        &admin,
+    )
+    .unwrap();
+    assert_eq!(
+        ctx.call::<u64>(ix::Multiply { a: 2, b: 2 }),
+        Err(error_code(ErrorCode::ProgramPaused))
+    );
+    assert_eq!(
+        ctx.call::<u128>(ix::MulU128 { a: 2, b: 2 }),
+        Err(error_code(ErrorCode::ProgramPaused))
+    );
+    assert_eq!(ctx.call::<u64>(ix::Add { a: 2, b: 2 }), Ok(4));
+
+    ctx.update_config(
+        ix::SetOperationPaused {
+            operation: OperationKind::Multiply,
+            paused: false,
+        },
+        &admin,
+    )
+    .unwrap();
+    assert_eq!(ctx.config().paused_operations, 0);
+    assert_eq!(ctx.call::<u64>(ix::Multiply { a: 2, b: 2 }), Ok(4));
+}
+
+#[test]
+fn history() {
+    let mut ctx = TestContext::new();
+    let user = ctx.new_user();
+    let history = common::history_address(&user.pubkey());
+    let init = common::instruction(
+        ix::InitializeHistory {},
+        test_program::accounts::InitializeHistory {
+            history,
+            authority: user.pubkey(),
+            system_program: solana_sdk::system_program::ID,
+        },
+    );
+    ctx.send::<()>(init, &[&user]).unwrap();
+
+    ctx.call_as::<u64>(ix::Add { a: 1, b: 2 }, Some(&user), Some(history))
+        .unwrap();
+    ctx.call_as::<u64>(ix::Max { a: 9, b: 4 }, Some(&user), Some(history))
+        .unwrap();
+    let recorded = ctx.history(&user.pubkey());
+    assert_eq!(recorded.len, 2);
+    assert_eq!(recorded.entries[0].result, 3);
+    assert_eq!(recorded.entries[1].operation, OperationKind::Max as u8);
+
+    // A history can only be written by its owner
+    let other = ctx.new_user();
+    assert_eq!(
+        ctx.call_as::<u64>(ix::Add { a: 1, b: 2 }, Some(&other), Some(history)),
+        Err(error_code(ErrorCode::Unauthorized))
+    );
+
+    let clear = common::instruction(
+        ix::ClearHistory {},
+        test_program::accounts::ClearHistory {
+            history,
+            authority: user.pubkey(),
+        },
+    );
+    ctx.send::<()>(clear, &[&user]).unwrap();
+    assert_eq!(ctx.history(&user.pubkey()).len, 0);
+}
+
+#[test]
+fn accumulator() {
+    let mut ctx = TestContext::new();
+    let user = ctx.new_user();
+    initialize_accumulator(&mut ctx, &user, 10);
+
+    assert_eq!(
+        accumulate(&mut ctx, &user, ix::AccumulateAdd { amount: 5 }),
+        Ok(15)
+    );
+    assert_eq!(
+        accumulate(&mut ctx, &user, ix::AccumulateMul { amount: 3 }),
+        Ok(45)
+    );
+    assert_eq!(
+        accumulate(&mut ctx, &user, ix::AccumulateSub { amount: 40 }),
+        Ok(5)
+    );
+    assert_eq!(
+        accumulate(&mut ctx, &user, ix::AccumulateMax { amount: 7 }),
+        Ok(7)
+    );
+    assert_eq!(
+        accumulate(&mut ctx, &user, ix::AccumulateSub { amount: 8 }),
+        Err(error_code(ErrorCode::Underflow))
+    );
+
+    let state = ctx.accumulator(&user.pubkey());
+    assert_eq!(state.authority, user.pubkey());
+    assert_eq!(state.value, 7);
+    assert_eq!(state.op_count, 4);
+}
+
+#[test]
+fn accumulator_caller_allowlist() {
+    let mut ctx = TestContext::new();
+    let admin = ctx.payer.insecure_clone();
+    let user = ctx.new_user();
+    initialize_accumulator(&mut ctx, &user, 10);
+    ctx.initialize_config();
+
+    ctx.update_config(ix::SetCallerRestriction { enabled: true }, &admin)
+        .unwrap();
+    assert_eq!(
+        accumulate(&mut ctx, &user, ix::AccumulateAdd { amount: 5 }),
+        Err(error_code(ErrorCode::Unauthorized))
+    );
+    assert_eq!(ctx.accumulator(&user.pubkey()).value, 10);
+
+    ctx.update_config(
+        ix::AddCaller {
+            caller: user.pubkey(),
+        },
+        &admin,
+    )
+    .unwrap();
+    assert_eq!(
+        accumulate(&mut ctx, &user, ix::AccumulateAdd { amount: 5 }),
+        Ok(15)
+    );
+}
+
+fn initialize_accumulator(ctx: &mut TestContext, user: &Keypair, initial: u64) {
+    let init = common::instruction(
+        ix::InitializeAccumulator { initial },
+        test_program::accounts::InitializeAccumulator {
+            accumulator: common::accumulator_address(&user.pubkey()),
+            authority: user.pubkey(),
+            system_program: solana_sdk::system_program::ID,
+        },
+    );
+    ctx.send::<()>(init, &[user]).unwrap();
+}
+
+fn accumulate(
+    ctx: &mut TestContext,
+    user: &Keypair,
+    data: impl InstructionData,
+) -> Result<u64, u32> {
+    let accounts = test_program::accounts::Accumulate {
+        accumulator: common::accumulator_address(&user.pubkey()),
+        authority: user.pubkey(),
+        config: common::config_address(),
+    };
+    ctx.send(common::instruction(data, accounts), &[user])
+}
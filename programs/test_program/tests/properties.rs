@@ -0,0 +1,169 @@
+//! Property tests comparing instruction results against plain Rust reference
+//! implementations across random inputs.
+
+mod common;
+
+use std::cell::RefCell;
+
+use common::{error_code, TestContext};
+use proptest::prelude::*;
+use test_program::{
+    decimal::{Decimal, RoundingMode, MAX_SCALE},
+    instruction as ix, math, stats, ArithmeticMode, DivRem, ErrorCode,
+};
+
+fn expect(result: Option<u64>, error: ErrorCode) -> Result<u64, u32> {
+    result.ok_or(error_code(error))
+}
+
+fn config() -> ProptestConfig {
+    ProptestConfig::with_cases(256)
+}
+
+#[test]
+fn u64_instructions_match_reference() {
+    let ctx = RefCell::new(TestContext::new());
+    proptest!(config(), |(a in any::<u64>(), b in any::<u64>())| {
+        let mut ctx = ctx.borrow_mut();
+        prop_assert_eq!(ctx.call::<u64>(ix::Add { a, b }), expect(a.checked_add(b), ErrorCode::Overflow));
+        prop_assert_eq!(ctx.call::<u64>(ix::Subtract { a, b }), expect(a.checked_sub(b), ErrorCode::Underflow));
+        prop_assert_eq!(ctx.call::<u64>(ix::Multiply { a, b }), expect(a.checked_mul(b), ErrorCode::Overflow));
+        prop_assert_eq!(ctx.call::<u64>(ix::Max { a, b }), Ok(a.max(b)));
+        prop_assert_eq!(ctx.call::<u64>(ix::Min { a, b }), Ok(a.min(b)));
+        prop_assert_eq!(ctx.call::<u64>(ix::AbsDiff { a, b }), Ok(a.abs_diff(b)));
+        let midpoint = ((a as u128 + b as u128) / 2) as u64;
+        prop_assert_eq!(ctx.call::<u64>(ix::Midpoint { a, b }), Ok(midpoint));
+    });
+}
+
+#[test]
+fn arithmetic_modes_match_reference() {
+    let ctx = RefCell::new(TestContext::new());
+    proptest!(config(), |(a in any::<u64>(), b in any::<u64>())| {
+        let mut ctx = ctx.borrow_mut();
+        let mode = ArithmeticMode::Saturating;
+        prop_assert_eq!(ctx.call::<u64>(ix::AddWithMode { a, b, mode }), Ok(a.saturating_add(b)));
+        prop_assert_eq!(ctx.call::<u64>(ix::SubtractWithMode { a, b, mode }), Ok(a.saturating_sub(b)));
+        prop_assert_eq!(ctx.call::<u64>(ix::MultiplyWithMode { a, b, mode }), Ok(a.saturating_mul(b)));
+        let mode = ArithmeticMode::Wrapping;
+        prop_assert_eq!(ctx.call::<u64>(ix::AddWithMode { a, b, mode }), Ok(a.wrapping_add(b)));
+        prop_assert_eq!(ctx.call::<u64>(ix::SubtractWithMode { a, b, mode }), Ok(a.wrapping_sub(b)));
+        prop_assert_eq!(ctx.call::<u64>(ix::MultiplyWithMode { a, b, mode }), Ok(a.wrapping_mul(b)));
+    });
+}
+
+#[test]
+fn mul_div_instructions_match_reference() {
+    let ctx = RefCell::new(TestContext::new());
+    proptest!(config(), |(a in any::<u64>(), b in any::<u64>(), c in 1..=u64::MAX)| {
+        let mut ctx = ctx.borrow_mut();
+        let (product, c128) = (a as u128 * b as u128, c as u128);
+        let fit = |value: u128| expect(u64::try_from(value).ok(), ErrorCode::Overflow);
+        let floor = product / c128;
+        let ceil = product.div_ceil(c128);
+        let remainder = product % c128;
+        let round = if 2 * remainder > c128 || (2 * remainder == c128 && floor % 2 == 1) {
+            floor + 1
+        } else {
+            floor
+        };
+        prop_assert_eq!(ctx.call::<u64>(ix::MulDivFloor { a, b, c }), fit(floor));
+        prop_assert_eq!(ctx.call::<u64>(ix::MulDivCeil { a, b, c }), fit(ceil));
+        prop_assert_eq!(ctx.call::<u64>(ix::MulDivRound { a, b, c }), fit(round));
+    });
+}
+
+#[test]
+fn small_products_match_reference() {
+    // Uniform u64 pairs almost always overflow `multiply`, so cover the success path separately
+    let ctx = RefCell::new(TestContext::new());
+    proptest!(config(), |(a in 0..=u32::MAX as u64, b in 0..=u32::MAX as u64)| {
+        prop_assert_eq!(ctx.borrow_mut().call::<u64>(ix::Multiply { a, b }), Ok(a * b));
+    });
+}
+
+#[test]
+fn division_instructions_match_reference() {
+    let ctx = RefCell::new(TestContext::new());
+    proptest!(config(), |(a in any::<u64>(), b in prop_oneof![Just(0u64), any::<u64>(), 1..1000u64])| {
+        let mut ctx = ctx.borrow_mut();
+        prop_assert_eq!(ctx.call::<u64>(ix::Divide { a, b }), expect(a.checked_div(b), ErrorCode::DivideByZero));
+        prop_assert_eq!(ctx.call::<u64>(ix::Modulo { a, b }), expect(a.checked_rem(b), ErrorCode::DivideByZero));
+        let ceil = (b != 0).then(|| a.div_ceil(b));
+        prop_assert_eq!(ctx.call::<u64>(ix::DivCeil { a, b }), expect(ceil, ErrorCode::DivideByZero));
+        let div_rem = (b != 0).then(|| DivRem { quotient: a / b, remainder: a % b });
+        prop_assert_eq!(ctx.call::<DivRem>(ix::DivRem { a, b }), div_rem.ok_or(error_code(ErrorCode::DivideByZero)));
+    });
+}
+
+proptest! {
+    #[test]
+    fn isqrt_brackets_value(value in any::<u64>()) {
+        let root = math::isqrt(value) as u128;
+        prop_assert!(root * root <= value as u128);
+        prop_assert!((root + 1) * (root + 1) > value as u128);
+    }
+
+    #[test]
+    fn nth_root_brackets_value(value in any::<u64>(), n in 1..=65u32) {
+        let root = math::nth_root(value, n).unwrap() as u128;
+        prop_assert!(root.pow(n) <= value as u128);
+        prop_assert!((root + 1).checked_pow(n).is_none_or(|power| power > value as u128));
+    }
+
+    #[test]
+    fn stats_match_reference(values in prop::collection::vec(0..=u32::MAX as u64, 1..=stats::MAX_STATS_LEN)) {
+        // Small enough that the textbook formulas fit in u128
+        let count = values.len() as u128;
+        let sum: u128 = values.iter().map(|&value| value as u128).sum();
+        let squares: u128 = values.iter().map(|&value| value as u128 * value as u128).sum();
+        let mut sorted = values.clone();
+        sorted.sort_unstable();
+        let middle = sorted.len() / 2;
+        let median = if sorted.len() % 2 == 0 {
+            (sorted[middle - 1] + sorted[middle]) / 2
+        } else {
+            sorted[middle]
+        };
+
+        let stats = stats::compute(values).unwrap();
+        prop_assert_eq!(stats.sum, sum);
+        prop_assert_eq!(stats.mean as u128, sum / count);
+        prop_assert_eq!(stats.median, median);
+        prop_assert_eq!(stats.min, sorted[0]);
+        prop_assert_eq!(stats.max, sorted[sorted.len() - 1]);
+        prop_assert_eq!(stats.variance, (count * squares - sum * sum) / (count * count));
+    }
+
+    #[test]
+    fn mul_div_u128_matches_narrow_reference(a in any::<u64>(), b in any::<u64>(), c in 1..=u64::MAX) {
+        let expected = a as u128 * b as u128 / c as u128;
+        prop_assert_eq!(math::mul_div_u128(a as u128, b as u128, c as u128), Some(expected));
+    }
+
+    #[test]
+    fn mul_div_u128_inverts_multiplication(a in any::<u128>(), c in 1..=u128::MAX) {
+        // (a * c) / c == a whenever the quotient fits
+        prop_assert_eq!(math::mul_div_u128(a, c, c), Some(a));
+    }
+
+    #[test]
+    fn decimal_rescale_round_trips(mantissa in 0..=u64::MAX as u128, scale in 0..=MAX_SCALE / 2) {
+        let value = Decimal { mantissa, scale };
+        let widened = value.rescale(scale + MAX_SCALE / 2, RoundingMode::Strict).unwrap();
+        prop_assert_eq!(widened.rescale(scale, RoundingMode::Strict).unwrap(), value);
+    }
+
+    #[test]
+    fn decimal_rounding_brackets_exact_quotient(a in 1..=u64::MAX as u128, b in 1..=u64::MAX as u128) {
+        let a = Decimal { mantissa: a, scale: 6 };
+        let b = Decimal { mantissa: b, scale: 6 };
+        let floor = a.checked_div(b, 6, RoundingMode::Floor).unwrap();
+        let ceil = a.checked_div(b, 6, RoundingMode::Ceil).unwrap();
+        let half_even = a.checked_div(b, 6, RoundingMode::HalfEven).unwrap();
+        prop_assert!(ceil.mantissa - floor.mantissa <= 1);
+        prop_assert!(half_even == floor || half_even == ceil);
+        let exact = (a.mantissa * 1_000_000).is_multiple_of(b.mantissa);
+        prop_assert_eq!(exact, floor == ceil);
+    }
+}